@@ -0,0 +1,144 @@
+// jdpub single-file reader. Everything is stored locally, nothing is fetched.
+(function () {
+  "use strict";
+
+  const root = document.documentElement;
+  const body = document.body;
+  const storageKey = "jdpub:" + document.title;
+  const popup = document.getElementById("popup");
+  const furigana = document.getElementById("furigana-level");
+
+  // Saved reader state.
+  let state = {};
+  try {
+    state = JSON.parse(localStorage.getItem(storageKey) || "{}");
+  } catch (e) {
+    state = {};
+  }
+  function save() {
+    try {
+      localStorage.setItem(storageKey, JSON.stringify(state));
+    } catch (e) {
+      // Storage may be unavailable for local files in some browsers.
+    }
+  }
+
+  // Definition popups.
+  function showPopup(ref) {
+    const id = decodeURIComponent(ref.getAttribute("href").slice(1));
+    const note = document.getElementById(id);
+    if (!note) {
+      return;
+    }
+    popup.innerHTML = note.innerHTML;
+    popup.hidden = false;
+
+    const rect = ref.getBoundingClientRect();
+    const width = popup.offsetWidth;
+    const left = Math.min(
+      Math.max(window.scrollX + rect.left, window.scrollX + 8),
+      window.scrollX + document.documentElement.clientWidth - width - 8,
+    );
+    popup.style.left = left + "px";
+    popup.style.top = window.scrollY + rect.bottom + 4 + "px";
+  }
+
+  document.addEventListener("click", function (event) {
+    const ref = event.target.closest("a.noteref");
+    if (ref) {
+      event.preventDefault();
+      showPopup(ref);
+      return;
+    }
+    if (!event.target.closest("#popup")) {
+      popup.hidden = true;
+    }
+  });
+  document.addEventListener("keydown", function (event) {
+    if (event.key === "Escape") {
+      popup.hidden = true;
+    }
+  });
+
+  // Furigana by JLPT level. Lower levels are more difficult.
+  function applyFurigana(level) {
+    document.querySelectorAll("#content ruby").forEach(function (ruby) {
+      const word = ruby.closest("[data-jlpt]");
+      let show = level !== "none";
+      if (show && level !== "all" && word) {
+        show = parseInt(word.dataset.jlpt, 10) <= parseInt(level, 10);
+      }
+      ruby.classList.toggle("hide-rt", !show);
+    });
+  }
+  furigana.value = state.furigana || "all";
+  applyFurigana(furigana.value);
+  furigana.addEventListener("change", function () {
+    state.furigana = furigana.value;
+    applyFurigana(furigana.value);
+    save();
+  });
+
+  // Dark mode.
+  if (state.dark === undefined) {
+    state.dark = window.matchMedia("(prefers-color-scheme: dark)").matches;
+  }
+  root.classList.toggle("dark", state.dark);
+  document.getElementById("toggle-dark").addEventListener("click", function () {
+    state.dark = !state.dark;
+    root.classList.toggle("dark", state.dark);
+    save();
+  });
+
+  // Chapter sidebar.
+  document.getElementById("toggle-sidebar").addEventListener("click", function () {
+    if (window.matchMedia("(max-width: 48rem)").matches) {
+      body.classList.toggle("sidebar-open");
+    } else {
+      body.classList.toggle("sidebar-closed");
+    }
+  });
+  document.getElementById("sidebar").addEventListener("click", function (event) {
+    if (event.target.closest("a")) {
+      body.classList.remove("sidebar-open");
+    }
+  });
+
+  const chapters = Array.from(document.querySelectorAll("section.chapter"));
+  function markCurrentChapter() {
+    let current = chapters[0];
+    for (const chapter of chapters) {
+      if (chapter.getBoundingClientRect().top <= 64) {
+        current = chapter;
+      }
+    }
+    document.querySelectorAll("#sidebar a.current").forEach(function (link) {
+      link.classList.remove("current");
+    });
+    if (current) {
+      const link = document.querySelector('#sidebar a[href="#' + current.id + '"]');
+      if (link) {
+        link.classList.add("current");
+      }
+    }
+  }
+
+  // Reading position.
+  let pending = false;
+  window.addEventListener("scroll", function () {
+    if (pending) {
+      return;
+    }
+    pending = true;
+    window.requestAnimationFrame(function () {
+      pending = false;
+      state.position = window.scrollY / Math.max(1, root.scrollHeight);
+      markCurrentChapter();
+      save();
+    });
+  });
+  if (!window.location.hash && state.position) {
+    window.scrollTo(0, state.position * root.scrollHeight);
+  }
+  markCurrentChapter();
+})();
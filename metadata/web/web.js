@@ -0,0 +1,141 @@
+(function () {
+  "use strict";
+
+  var form = document.getElementById("convert");
+  var preview = document.getElementById("preview");
+  var previewButton = document.getElementById("preview-button");
+  var status = document.getElementById("status");
+  var previewTimer = null;
+  var busy = false;
+
+  function setStatus(text, error) {
+    status.textContent = text;
+    status.classList.toggle("error", !!error);
+  }
+
+  function readFile(file) {
+    return new Promise(function (resolve, reject) {
+      var reader = new FileReader();
+      reader.onload = function () {
+        // Strip the "data:<type>;base64," prefix.
+        var result = reader.result;
+        resolve({ name: file.name, data: result.slice(result.indexOf(",") + 1) });
+      };
+      reader.onerror = function () {
+        reject(reader.error);
+      };
+      reader.readAsDataURL(file);
+    });
+  }
+
+  function buildRequest() {
+    var files = Array.prototype.map.call(form.elements.files.files, readFile);
+    return Promise.all(files).then(function (files) {
+      return {
+        title: form.elements.title.value.trim(),
+        author: form.elements.author.value.trim(),
+        text: form.elements.text.value,
+        files: files,
+        urls: form.elements.urls.value.split("\n").map(function (url) {
+          return url.trim();
+        }).filter(function (url) {
+          return url.length > 0;
+        }),
+        language: {
+          japanese: {
+            definitions: form.elements.definitions.value,
+            furigana: form.elements.furigana.value,
+          },
+        },
+      };
+    });
+  }
+
+  function hasInput() {
+    return form.elements.text.value.trim().length > 0 ||
+      form.elements.files.files.length > 0 ||
+      form.elements.urls.value.trim().length > 0;
+  }
+
+  function post(path) {
+    return buildRequest().then(function (request) {
+      return fetch(path, {
+        method: "POST",
+        headers: { "Content-Type": "application/json" },
+        body: JSON.stringify(request),
+      });
+    }).then(function (res) {
+      if (!res.ok) {
+        return res.text().then(function (text) {
+          throw new Error(text || res.statusText);
+        });
+      }
+      return res;
+    });
+  }
+
+  function run(label, path, done) {
+    if (busy) {
+      return;
+    }
+    busy = true;
+    Array.prototype.forEach.call(form.querySelectorAll("button"), function (button) {
+      button.disabled = true;
+    });
+    setStatus(label);
+
+    post(path).then(done).then(function () {
+      setStatus("");
+    }).catch(function (e) {
+      setStatus(e.message, true);
+    }).finally(function () {
+      busy = false;
+      Array.prototype.forEach.call(form.querySelectorAll("button"), function (button) {
+        button.disabled = false;
+      });
+    });
+  }
+
+  function updatePreview() {
+    if (!hasInput()) {
+      preview.srcdoc = "";
+      return;
+    }
+    run("Annotating…", "/preview", function (res) {
+      return res.text().then(function (html) {
+        preview.srcdoc = html;
+      });
+    });
+  }
+
+  function download(event) {
+    event.preventDefault();
+    run("Building epub…", "/epub", function (res) {
+      return res.blob().then(function (blob) {
+        var link = document.createElement("a");
+        link.href = URL.createObjectURL(blob);
+        link.download = (form.elements.title.value.trim() || "jdpub") + ".epub";
+        document.body.appendChild(link);
+        link.click();
+        link.remove();
+        setTimeout(function () {
+          URL.revokeObjectURL(link.href);
+        }, 1000);
+      });
+    });
+  }
+
+  function schedulePreview() {
+    clearTimeout(previewTimer);
+    previewTimer = setTimeout(updatePreview, 800);
+  }
+
+  form.addEventListener("submit", download);
+  previewButton.addEventListener("click", updatePreview);
+  // Only pasted text and annotation levels update the preview live; files and
+  // urls can be slow to convert.
+  form.elements.text.addEventListener("input", schedulePreview);
+  Array.prototype.forEach.call(form.querySelectorAll("select, input[type=checkbox]"), function (input) {
+    input.addEventListener("change", schedulePreview);
+  });
+})();
@@ -9,6 +9,10 @@ pub struct Book {
 pub struct Chapter {
     pub title: Option<String>,
     pub ast: durf::Ast,
+    /// Chapter language, if different from the book's language.
+    pub language: Option<Language>,
+    /// Pitch accents of the annotated words.
+    pub pitch: AnnotatedPitch,
     // pub html: html::
     // pub html: crate:
 }
@@ -4,27 +4,99 @@ use super::*;
 
 /// Annotate documents with readings and definitions.
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, subcommand_precedence_over_arg = true)]
 pub struct Cli {
-    /// Input chapters or book files.
+    /// Subcommand to run instead of converting the inputs.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Input chapters or book files. Inputs named like a command go after `--`
     pub input: Vec<String>,
     /// Output file.
     #[arg(short, long)]
     pub output: Option<PathBuf>,
     /// Additional configuration file, parsed first.
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     config: Option<PathBuf>,
     /// Debug flag.
-    #[arg(short, long, default_value_t = false)]
+    #[arg(short, long, global = true, default_value_t = false)]
     pub debug: bool,
     /// Verbose flag.
-    #[arg(short, long, default_value_t = false)]
+    #[arg(short, long, global = true, default_value_t = false)]
     pub verbose: bool,
     /// Skip built-in configurations.
-    #[arg(long, default_value_t = false)]
+    #[arg(long, global = true, default_value_t = false)]
     pub do_not_use_builtin: bool,
 }
 
+/// Subcommands.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Start a local web interface.
+    Serve(ServeArgs),
+    /// Show vocabulary statistics for the inputs.
+    Stats(StatsArgs),
+    /// Explain how the words of some text are annotated.
+    Lookup(LookupArgs),
+}
+
+/// Web interface arguments.
+#[derive(Args, Debug)]
+pub struct ServeArgs {
+    /// Address to listen on.
+    #[arg(long, default_value = "127.0.0.1")]
+    pub host: String,
+    /// Port to listen on.
+    #[arg(short, long, default_value_t = 8080)]
+    pub port: u16,
+    /// Also serve the JSON API.
+    #[arg(long, default_value_t = false)]
+    pub api: bool,
+    /// Origin allowed to call the JSON API from a browser, e.g. a browser
+    /// extension. May be repeated.
+    #[arg(long = "allow-origin")]
+    pub allow_origins: Vec<String>,
+    /// Configuration files applied to every conversion.
+    pub input: Vec<String>,
+}
+
+/// Statistics arguments.
+#[derive(Args, Debug)]
+pub struct StatsArgs {
+    /// Print the statistics as JSON.
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+    /// Number of unknown words to list.
+    #[arg(long, default_value_t = 20)]
+    pub top: usize,
+    /// Input chapters, book files or configuration files.
+    pub input: Vec<String>,
+}
+
+/// Lookup arguments.
+#[derive(Args, Debug)]
+pub struct LookupArgs {
+    /// Text to look up.
+    #[arg(required = true)]
+    pub text: Vec<String>,
+    /// Print the lookups as JSON.
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+    /// Additional configuration files, such as glossaries or known words.
+    #[arg(short, long)]
+    pub input: Vec<String>,
+}
+
+impl Command {
+    /// Inputs given after the subcommand.
+    pub fn input(&self) -> &[String] {
+        match self {
+            Self::Serve(args) => &args.input,
+            Self::Stats(args) => &args.input,
+            Self::Lookup(args) => &args.input,
+        }
+    }
+}
+
 impl Cli {
     pub fn new() -> Result<Self> {
         Ok(Self::parse())
@@ -38,41 +110,17 @@ impl Cli {
 
         // If another config was specified, merge it.
         if let Some(config_path) = &self.config {
-            let config_text = match std::fs::read_to_string(config_path) {
-                Ok(data) => data,
-                Err(e) => bail!("Failed to read config file: {e}"),
-            };
-            let parsed_config: Config = match toml::from_str(&config_text) {
-                Ok(c) => c,
-                Err(e) => bail!("Failed to parse config file: {e}"),
-            };
-
-            tracing::debug!(
-                "Successfully parsed config file '{}'.",
-                config_path.to_string_lossy()
-            );
-
-            config.merge(parsed_config)?;
+            config.merge(Config::from_file(config_path)?)?;
         } else {
             tracing::debug!("No config file specified.");
         }
 
-        // Add inputs.
-        for input_file in self.input.iter() {
+        // Add inputs, both before and after the subcommand.
+        let command_input = self.command.as_ref().map(Command::input).unwrap_or_default();
+        for input_file in self.input.iter().chain(command_input) {
             if input_file.ends_with(".toml") {
                 // Toml inputs are essentially additional configuration files.
-                let config_text = match std::fs::read_to_string(&input_file) {
-                    Ok(data) => data,
-                    Err(e) => bail!("Failed to read config file: {e}"),
-                };
-                let parsed_config: Config = match toml::from_str(&config_text) {
-                    Ok(c) => c,
-                    Err(e) => bail!("Failed to parse config file: {e}"),
-                };
-
-                tracing::debug!("Successfully parsed config file '{}'.", input_file);
-
-                config.merge(parsed_config)?;
+                config.merge(Config::from_file(input_file)?)?;
             } else {
                 // Otherwise we use the filse as a new chapter.
                 config.import.chapters.push(ChapterConfig {
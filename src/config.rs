@@ -35,6 +35,25 @@ impl Config {
         Ok(config)
     }
 
+    /// Read a configuration file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let config_text = match std::fs::read_to_string(path.as_ref()) {
+            Ok(data) => data,
+            Err(e) => bail!("Failed to read config file: {e}"),
+        };
+        let config: Config = match toml::from_str(&config_text) {
+            Ok(c) => c,
+            Err(e) => bail!("Failed to parse config file: {e}"),
+        };
+
+        tracing::debug!(
+            "Successfully parsed config file '{}'.",
+            path.as_ref().to_string_lossy()
+        );
+
+        Ok(config)
+    }
+
     /// Merge configuration files.
     /// This needs to be improved to support only overriding sections that are
     /// specified.
@@ -45,8 +64,8 @@ impl Config {
         // Merge import section.
         self.import.chapters.extend(other.import.chapters);
 
-        // Merge language support. TODO
-        self.language = other.language;
+        // Merge language support.
+        self.language.merge(other.language)?;
 
         // Merge export.
         self.export.merge(other.export)?;
@@ -105,6 +124,14 @@ impl HtmlParseConfig {
 }
 
 impl HtmlParseConfig {
+    /// Check if no parse setting is changed from the defaults.
+    pub fn is_default(&self) -> bool {
+        (self.user_agent.is_empty() || self.user_agent == Self::default().user_agent)
+            && self.allow.is_empty()
+            && self.skip.is_empty()
+            && self.depth.is_none()
+    }
+
     pub fn depth(&self) -> usize {
         self.depth.unwrap_or(10)
     }
@@ -139,62 +166,418 @@ impl Default for HtmlParseConfig {
 }
 
 /// Language configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct LanguageConfig {
+    /// Language of the book. Chapters may override this.
+    #[serde(default)]
+    language: Option<Language>,
     /// Use approximate lookups and definitions.
-    pub approximate: bool,
+    #[serde(default)]
+    approximate: Option<bool>,
+    /// Most adjacent tokens to join when looking for set phrases and
+    /// compounds. Set to 1 to look up each token on its own.
+    #[serde(default, alias = "max-span")]
+    max_span: Option<usize>,
+    /// Dictionaries, in the order they are consulted.
+    #[serde(default, alias = "dictionary")]
+    pub dictionaries: Vec<DictionaryConfig>,
+    /// Names invented by the author, which take priority over dictionaries.
+    #[serde(default, alias = "name")]
+    pub names: Vec<NameConfig>,
+    /// Glossary of series terms, which take priority over dictionaries.
+    #[serde(default)]
+    pub glossary: Vec<GlossaryEntry>,
+    /// Glossary csv files, with word, reading, meaning and level columns.
+    #[serde(default, alias = "glossary-files", alias = "glossary-file")]
+    pub glossary_files: Vec<PathBuf>,
+    /// Word lists of words the reader already knows, one word per line, for
+    /// `jdpub stats`.
+    #[serde(default, alias = "known-words")]
+    pub known_words: Vec<PathBuf>,
+    /// Frequency rank thresholds.
+    #[serde(default)]
+    pub frequency: FrequencyConfig,
     /// Japanese language configuration.
     #[serde(default)]
     pub japanese: JapaneseLanguageConfig,
+    /// Chinese language configuration.
+    #[serde(default, alias = "mandarin")]
+    pub chinese: ChineseLanguageConfig,
 }
 
-impl Default for LanguageConfig {
-    fn default() -> Self {
-        Self {
-            approximate: true,
-            japanese: JapaneseLanguageConfig::default(),
+impl LanguageConfig {
+    pub fn language(&self) -> Language {
+        self.language.unwrap_or_default()
+    }
+
+    pub fn approximate(&self) -> bool {
+        self.approximate.unwrap_or(true)
+    }
+
+    pub fn max_span(&self) -> usize {
+        self.max_span.unwrap_or(4)
+    }
+
+    /// Merge another layer. Names, glossaries and known words accumulate so
+    /// that series and book configs can both add entries, dictionaries are
+    /// replaced by a layer listing any and other settings only when set.
+    fn merge(&mut self, other: LanguageConfig) -> Result<()> {
+        if let Some(language) = other.language {
+            self.language = Some(language);
         }
+        if let Some(approximate) = other.approximate {
+            self.approximate = Some(approximate);
+        }
+        if let Some(max_span) = other.max_span {
+            self.max_span = Some(max_span);
+        }
+        if !other.dictionaries.is_empty() {
+            self.dictionaries = other.dictionaries;
+        }
+        self.names.extend(other.names);
+        self.glossary.extend(other.glossary);
+        self.glossary_files.extend(other.glossary_files);
+        self.known_words.extend(other.known_words);
+        self.frequency.merge(other.frequency)?;
+        self.japanese.merge(other.japanese)?;
+        self.chinese.merge(other.chinese)?;
+
+        Ok(())
     }
+
+    /// Merge the settings of a web request. The dictionaries are loaded once
+    /// when the server starts, so a request may only change the language and
+    /// the thresholds for annotating words.
+    pub fn merge_request(&mut self, other: LanguageConfig) -> Result<()> {
+        let fixed = [
+            ("approximate", other.approximate.is_some()),
+            ("dictionaries", !other.dictionaries.is_empty()),
+            ("names", !other.names.is_empty()),
+            ("glossary", !other.glossary.is_empty()),
+            ("glossary-files", !other.glossary_files.is_empty()),
+            ("known-words", !other.known_words.is_empty()),
+            ("japanese.names", other.japanese.names.is_some()),
+            ("japanese.kanjidic", other.japanese.kanjidic.is_some()),
+            ("japanese.kanji-grade", other.japanese.kanji_grade.is_some()),
+            ("japanese.accents", other.japanese.accents.is_some()),
+            ("japanese.tokenizer", other.japanese.tokenizer.is_some()),
+            ("japanese.lindera-mode", other.japanese.lindera_mode.is_some()),
+            ("japanese.deinflection", other.japanese.deinflection.is_some()),
+            ("chinese.dictionary", other.chinese.dictionary.is_some()),
+        ];
+        let fixed: Vec<&str> = fixed
+            .into_iter()
+            .filter_map(|(name, set)| set.then_some(name))
+            .collect();
+        if !fixed.is_empty() {
+            bail!(
+                "{} can only be set in the server's configuration.",
+                fixed.join(", ")
+            );
+        }
+
+        self.merge(other)
+    }
+}
+
+/// Supported languages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    /// Japanese.
+    #[default]
+    #[serde(alias = "ja", alias = "jp")]
+    Japanese,
+    /// Mandarin Chinese.
+    #[serde(alias = "zh", alias = "mandarin")]
+    Chinese,
+}
+
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Japanese => write!(f, "Japanese"),
+            Self::Chinese => write!(f, "Chinese"),
+        }
+    }
+}
+
+/// Name configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct NameConfig {
+    /// Name as written.
+    #[serde(alias = "word")]
+    pub name: String,
+    /// Reading of the name.
+    pub reading: String,
+    /// Kind of name, e.g. person or place.
+    #[serde(default)]
+    pub kind: Option<String>,
+}
+
+/// Glossary entry for a term, e.g. from a per-series term list.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GlossaryEntry {
+    /// Term as written.
+    #[serde(alias = "term")]
+    pub word: String,
+    /// Reading of the term. If empty, the dictionary reading is used.
+    #[serde(default)]
+    pub reading: String,
+    /// Meaning of the term. If empty, the dictionary meaning is used.
+    #[serde(default, alias = "definition")]
+    pub meaning: String,
+    /// Difficulty level, e.g. `N3` or `HSK 4`. If unset, the dictionary
+    /// level is used, and unknown terms are always annotated.
+    #[serde(default)]
+    pub level: Option<GlossaryLevel>,
+}
+
+/// Glossary difficulty level, as a number or a level name.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum GlossaryLevel {
+    Number(u8),
+    Name(String),
+}
+
+impl GlossaryLevel {
+    /// Get the level as a JLPT level.
+    pub fn jlpt(&self) -> Option<JlptLevel> {
+        match self {
+            Self::Number(n) => Some(JlptLevel::from(*n)),
+            Self::Name(name) => name.parse().ok(),
+        }
+    }
+
+    /// Get the level as an HSK level.
+    pub fn hsk(&self) -> Option<HskLevel> {
+        match self {
+            Self::Number(n) => Some(HskLevel::from(*n)),
+            Self::Name(name) => name.parse().ok(),
+        }
+    }
+}
+
+/// Dictionary configuration. Either a path or a built-in dictionary is set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DictionaryConfig {
+    /// Local dictionary file, e.g. a Yomitan dictionary zip, or a frequency
+    /// list csv with one word per line, most frequent first.
+    #[serde(default, alias = "file")]
+    pub path: Option<PathBuf>,
+    /// Built-in dictionary, to change where it is consulted. By default the
+    /// vocabulary lists are consulted first and the dictionary last.
+    #[serde(default)]
+    pub builtin: Option<BuiltinDictionary>,
+    /// Language of the dictionary. This defaults to the book's language.
+    #[serde(default, alias = "lang")]
+    pub language: Option<Language>,
+}
+
+/// Built-in dictionaries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BuiltinDictionary {
+    /// Vocabulary lists with difficulty levels, e.g. JLPT or HSK lists.
+    #[serde(alias = "vocabulary", alias = "jlpt", alias = "hsk")]
+    Common,
+    /// Default dictionary, e.g. jmdict or CC-CEDICT.
+    #[serde(alias = "jmdict", alias = "cedict")]
+    Dictionary,
+}
+
+/// Frequency configuration, to find difficult words by their frequency rank
+/// in the loaded frequency lists or dictionaries.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FrequencyConfig {
+    /// How frequency ranks combine with the difficulty levels.
+    #[serde(default)]
+    mode: Option<FrequencyMode>,
+    /// Add definitions to words ranked below this, e.g. 10000. Words missing
+    /// from the frequency lists are ranked below every threshold.
+    #[serde(default, alias = "tooltips")]
+    pub definitions: Option<u64>,
+    /// Add readings to words ranked below this.
+    #[serde(default, alias = "furigana", alias = "annotations")]
+    pub readings: Option<u64>,
+}
+
+impl FrequencyConfig {
+    fn merge(&mut self, other: Self) -> Result<()> {
+        if let Some(mode) = other.mode {
+            self.mode = Some(mode);
+        }
+        if let Some(definitions) = other.definitions {
+            self.definitions = Some(definitions);
+        }
+        if let Some(readings) = other.readings {
+            self.readings = Some(readings);
+        }
+
+        Ok(())
+    }
+
+    pub fn mode(&self) -> FrequencyMode {
+        self.mode.unwrap_or_default()
+    }
+}
+
+/// How frequency ranks combine with the difficulty levels, e.g. JLPT levels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FrequencyMode {
+    /// Only use ranks for words without a level.
+    #[default]
+    Fallback,
+    /// Only use ranks, ignoring levels.
+    Replace,
+    /// Annotate words that are difficult by both level and rank.
+    #[serde(alias = "and")]
+    Both,
 }
 
 /// Japanese language configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct JapaneseLanguageConfig {
     /// JLPT level for definitions.
     #[serde(default, alias = "jlpt-level", alias = "tooltips")]
-    definitions: SerializedJlptLevel,
+    definitions: Option<SerializedJlptLevel>,
     /// JLPT level for furigana.
     #[serde(default, alias = "annotations")]
-    furigana: SerializedJlptLevel,
+    furigana: Option<SerializedJlptLevel>,
+    /// Local names dictionary, either JMnedict in the jmdict-simplified json
+    /// format or a Yomitan dictionary zip.
+    #[serde(default, alias = "jmnedict")]
+    pub names: Option<PathBuf>,
+    /// Local KANJIDIC2 xml file, for kanji meanings, readings and grades in
+    /// tooltips.
+    #[serde(default)]
+    pub kanjidic: Option<PathBuf>,
+    /// Only annotate words with kanji above this school grade: 1 to 6 for the
+    /// kyōiku grades, 8 for the remaining jōyō kanji and 9 or 10 for jinmeiyō
+    /// kanji. Kanji without a grade are above every grade. Needs `kanjidic`.
+    #[serde(default, alias = "kanji-grade")]
+    pub kanji_grade: Option<u8>,
+    /// Local pitch accent tsv in the Kanjium format, with word, reading and
+    /// downstep columns.
+    #[serde(default, alias = "pitch-accents", alias = "pitch-accent")]
+    pub accents: Option<PathBuf>,
+    /// Tokenizer used to split text into words.
+    #[serde(default)]
+    tokenizer: Option<JapaneseTokenizer>,
+    /// Lindera's segmentation mode, deciding whether the Lindera tokenizers
+    /// split long compounds.
+    #[serde(default, alias = "lindera-mode")]
+    lindera_mode: Option<LinderaMode>,
+    /// Show the conjugations of deinflected words in tooltips, e.g.
+    /// 食べる → causative → passive → negative → past.
+    #[serde(default, alias = "conjugations")]
+    deinflection: Option<bool>,
 }
 
 impl JapaneseLanguageConfig {
+    fn merge(&mut self, other: Self) -> Result<()> {
+        if let Some(definitions) = other.definitions {
+            self.definitions = Some(definitions);
+        }
+        if let Some(furigana) = other.furigana {
+            self.furigana = Some(furigana);
+        }
+        if let Some(names) = other.names {
+            self.names = Some(names);
+        }
+        if let Some(kanjidic) = other.kanjidic {
+            self.kanjidic = Some(kanjidic);
+        }
+        if let Some(kanji_grade) = other.kanji_grade {
+            self.kanji_grade = Some(kanji_grade);
+        }
+        if let Some(accents) = other.accents {
+            self.accents = Some(accents);
+        }
+        if let Some(tokenizer) = other.tokenizer {
+            self.tokenizer = Some(tokenizer);
+        }
+        if let Some(lindera_mode) = other.lindera_mode {
+            self.lindera_mode = Some(lindera_mode);
+        }
+        if let Some(deinflection) = other.deinflection {
+            self.deinflection = Some(deinflection);
+        }
+
+        Ok(())
+    }
+
     /// Get the least difficult level setting.
     pub fn lowest_level(&self) -> JlptLevel {
         self.definitions().max(self.furigana())
     }
 
     pub fn definitions(&self) -> JlptLevel {
-        (&self.definitions).into()
+        self.definitions.as_ref().map_or(JlptLevel::N3, Into::into)
     }
 
     pub fn furigana(&self) -> JlptLevel {
-        (&self.furigana).into()
+        self.furigana.as_ref().map_or(JlptLevel::N3, Into::into)
+    }
+
+    pub fn tokenizer(&self) -> JapaneseTokenizer {
+        self.tokenizer.unwrap_or_default()
+    }
+
+    pub fn lindera_mode(&self) -> LinderaMode {
+        self.lindera_mode.unwrap_or_default()
+    }
+
+    pub fn deinflection(&self) -> bool {
+        self.deinflection.unwrap_or_default()
     }
 }
 
-impl Default for JapaneseLanguageConfig {
-    fn default() -> Self {
-        Self {
-            definitions: SerializedJlptLevel::Level(JlptLevel::N3),
-            furigana: SerializedJlptLevel::Level(JlptLevel::N3),
+/// Japanese tokenizers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum JapaneseTokenizer {
+    /// charabia's default segmenter.
+    #[default]
+    Charabia,
+    /// Lindera with IPADIC, which needs the `lindera-ipadic` feature.
+    #[serde(alias = "lindera", alias = "ipadic")]
+    LinderaIpadic,
+    /// Lindera with UniDic, which needs the `lindera-unidic` feature.
+    #[serde(alias = "unidic")]
+    LinderaUnidic,
+}
+
+impl std::fmt::Display for JapaneseTokenizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Charabia => write!(f, "charabia"),
+            Self::LinderaIpadic => write!(f, "lindera-ipadic"),
+            Self::LinderaUnidic => write!(f, "lindera-unidic"),
         }
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Lindera segmentation modes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinderaMode {
+    /// Keep the dictionary's words, e.g. 東京国際空港.
+    #[default]
+    Normal,
+    /// Split long compound nouns, e.g. 東京, 国際 and 空港.
+    Decompose,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(untagged)]
 enum SerializedJlptLevel {
     Level(JlptLevel),
@@ -217,7 +600,7 @@ impl Default for SerializedJlptLevel {
 }
 
 /// JLPT level.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, PartialOrd, Eq, Ord)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, PartialOrd, Eq, Ord, JsonSchema)]
 pub enum JlptLevel {
     /// None.
     #[serde(alias = "none", alias = "null")]
@@ -284,6 +667,168 @@ impl std::fmt::Display for JlptLevel {
     }
 }
 
+impl std::str::FromStr for JlptLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match s.trim() {
+            "N+" | "n+" | "beginner" => Self::Beginner,
+            "N5" | "n5" | "5" => Self::N5,
+            "N4" | "n4" | "4" => Self::N4,
+            "N3" | "n3" | "3" => Self::N3,
+            "N2" | "n2" | "2" => Self::N2,
+            "N1" | "n1" | "1" => Self::N1,
+            "Master" | "master" | "0" => Self::Master,
+            "None" | "none" => Self::None,
+            _ => bail!("Invalid JLPT level: {s}"),
+        })
+    }
+}
+
+/// Chinese language configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ChineseLanguageConfig {
+    /// Local CC-CEDICT file for words outside of the HSK lists.
+    #[serde(default, alias = "cedict", alias = "cc-cedict")]
+    pub dictionary: Option<PathBuf>,
+    /// HSK level for definitions.
+    #[serde(default, alias = "hsk-level", alias = "tooltips")]
+    definitions: Option<SerializedHskLevel>,
+    /// HSK level for pinyin.
+    #[serde(default, alias = "annotations")]
+    pinyin: Option<SerializedHskLevel>,
+}
+
+impl ChineseLanguageConfig {
+    fn merge(&mut self, other: Self) -> Result<()> {
+        if let Some(dictionary) = other.dictionary {
+            self.dictionary = Some(dictionary);
+        }
+        if let Some(definitions) = other.definitions {
+            self.definitions = Some(definitions);
+        }
+        if let Some(pinyin) = other.pinyin {
+            self.pinyin = Some(pinyin);
+        }
+
+        Ok(())
+    }
+
+    pub fn definitions(&self) -> HskLevel {
+        self.definitions.as_ref().map_or(HskLevel::Hsk3, Into::into)
+    }
+
+    pub fn pinyin(&self) -> HskLevel {
+        self.pinyin.as_ref().map_or(HskLevel::Hsk3, Into::into)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum SerializedHskLevel {
+    Level(HskLevel),
+    Number(u8),
+}
+
+impl From<&SerializedHskLevel> for HskLevel {
+    fn from(value: &SerializedHskLevel) -> Self {
+        match value {
+            SerializedHskLevel::Level(l) => *l,
+            SerializedHskLevel::Number(n) => HskLevel::from(*n),
+        }
+    }
+}
+
+impl Default for SerializedHskLevel {
+    fn default() -> Self {
+        Self::Level(HskLevel::default())
+    }
+}
+
+/// HSK level. Unlike JLPT levels, higher levels are more difficult.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, PartialOrd, Eq, Ord, JsonSchema)]
+pub enum HskLevel {
+    /// Need everything annotated.
+    #[serde(alias = "beginner")]
+    Beginner = 0,
+    /// HSK 1 level.
+    #[serde(alias = "hsk1", alias = "HSK1")]
+    Hsk1 = 1,
+    /// HSK 2 level.
+    #[serde(alias = "hsk2", alias = "HSK2")]
+    Hsk2 = 2,
+    /// HSK 3 level.
+    #[default]
+    #[serde(alias = "hsk3", alias = "HSK3")]
+    Hsk3 = 3,
+    /// HSK 4 level.
+    #[serde(alias = "hsk4", alias = "HSK4")]
+    Hsk4 = 4,
+    /// HSK 5 level.
+    #[serde(alias = "hsk5", alias = "HSK5")]
+    Hsk5 = 5,
+    /// HSK 6 level.
+    #[serde(alias = "hsk6", alias = "HSK6")]
+    Hsk6 = 6,
+    /// Not in the HSK lists, or mastered everything.
+    #[serde(alias = "none", alias = "master")]
+    None = 7,
+}
+
+impl From<u8> for HskLevel {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Beginner,
+            1 => Self::Hsk1,
+            2 => Self::Hsk2,
+            3 => Self::Hsk3,
+            4 => Self::Hsk4,
+            5 => Self::Hsk5,
+            6 => Self::Hsk6,
+            _ => Self::None,
+        }
+    }
+}
+
+impl std::fmt::Display for HskLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                Self::Beginner => "Beginner",
+                Self::Hsk1 => "HSK 1",
+                Self::Hsk2 => "HSK 2",
+                Self::Hsk3 => "HSK 3",
+                Self::Hsk4 => "HSK 4",
+                Self::Hsk5 => "HSK 5",
+                Self::Hsk6 => "HSK 6",
+                Self::None => "None",
+            },
+        )
+    }
+}
+
+impl std::str::FromStr for HskLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let level = s.trim().to_lowercase().replace(' ', "");
+        Ok(match level.strip_prefix("hsk").unwrap_or(&level) {
+            "beginner" => Self::Beginner,
+            "1" => Self::Hsk1,
+            "2" => Self::Hsk2,
+            "3" => Self::Hsk3,
+            "4" => Self::Hsk4,
+            "5" => Self::Hsk5,
+            "6" => Self::Hsk6,
+            "none" | "master" => Self::None,
+            _ => bail!("Invalid HSK level: {s}"),
+        })
+    }
+}
+
 /// Import configuration.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -303,6 +848,9 @@ pub struct ChapterConfig {
     /// Chapter uri.
     #[serde(alias = "path", alias = "url", alias = "file")]
     pub uri: String,
+    /// Chapter language, if different from the book's language.
+    #[serde(default, alias = "lang")]
+    pub language: Option<Language>,
 }
 
 /// Export configuration.
@@ -321,6 +869,16 @@ pub struct ExportConfig {
     /// The output file.
     #[serde(default, alias = "file", alias = "output", alias = "path")]
     pub output_file: PathBuf,
+    /// Table of contents configuration.
+    #[serde(default)]
+    pub toc: TocConfig,
+    /// Reading notation for text exports.
+    #[serde(default, alias = "readings", alias = "reading-notation")]
+    pub reading_notation: Option<ReadingNotation>,
+    /// Mark the pitch accent of readings with overlines in html and epub
+    /// exports. Needs pitch accents to be loaded.
+    #[serde(default, alias = "pitch-accent")]
+    pub pitch_accent: Option<bool>,
 }
 
 impl ExportConfig {
@@ -337,6 +895,13 @@ impl ExportConfig {
         if !other.output_file.to_string_lossy().is_empty() {
             self.output_file = other.output_file;
         }
+        self.toc.merge(other.toc)?;
+        if let Some(reading_notation) = other.reading_notation {
+            self.reading_notation = Some(reading_notation);
+        }
+        if let Some(pitch_accent) = other.pitch_accent {
+            self.pitch_accent = Some(pitch_accent);
+        }
 
         Ok(())
     }
@@ -351,10 +916,18 @@ impl ExportConfig {
 
     pub fn export_type(&self) -> ExportType {
         let output_lossy = self.output_file.to_string_lossy();
-        if output_lossy.ends_with(".epub") {
+        if output_lossy.ends_with('/') || self.output_file.is_dir() {
+            return ExportType::Site;
+        } else if output_lossy.ends_with(".kepub.epub") {
+            return ExportType::Kepub;
+        } else if output_lossy.ends_with(".epub") {
             return ExportType::Epub;
         } else if output_lossy.ends_with(".html") {
             return ExportType::Html;
+        } else if output_lossy.ends_with(".txt") {
+            return ExportType::Text;
+        } else if output_lossy.ends_with(".md") || output_lossy.ends_with(".markdown") {
+            return ExportType::Markdown;
         }
 
         tracing::debug!("Failed to derive export type, using epub.");
@@ -362,12 +935,57 @@ impl ExportConfig {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Table of contents configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TocConfig {
+    /// Maximum depth of the table of contents. Chapters are depth 1 and
+    /// headings inside of chapters are nested below them.
+    #[serde(default)]
+    depth: Option<usize>,
+    /// Generate an inline table of contents page.
+    #[serde(default, alias = "inline-toc")]
+    inline: Option<bool>,
+}
+
+impl TocConfig {
+    fn merge(&mut self, other: Self) -> Result<()> {
+        if let Some(depth) = other.depth {
+            self.depth = Some(depth);
+        }
+        if let Some(inline) = other.inline {
+            self.inline = Some(inline);
+        }
+
+        Ok(())
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth.unwrap_or(3)
+    }
+
+    pub fn inline(&self) -> bool {
+        self.inline.unwrap_or(true)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ExportType {
     #[serde(alias = "epub")]
     Epub,
+    /// Kobo epub.
+    #[serde(alias = "kepub")]
+    Kepub,
     Html,
-    // Markdown,
+    /// Plain text.
+    #[serde(alias = "text", alias = "txt")]
+    Text,
+    /// Markdown.
+    #[serde(alias = "markdown", alias = "md")]
+    Markdown,
+    /// Static html site, written to a directory.
+    #[serde(alias = "site")]
+    Site,
 }
 
 impl Default for ExportType {
@@ -375,3 +993,17 @@ impl Default for ExportType {
         Self::Epub
     }
 }
+
+/// Notation for readings in text exports.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ReadingNotation {
+    /// Readings in parentheses, e.g. `漢字(かんじ)`.
+    #[serde(alias = "parentheses", alias = "parens")]
+    Parentheses,
+    /// Aozora Bunko style readings, e.g. `｜漢字《かんじ》`.
+    #[serde(alias = "aozora")]
+    Aozora,
+    /// Html ruby elements, e.g. `<ruby>漢字<rt>かんじ</rt></ruby>`.
+    #[serde(alias = "ruby")]
+    Ruby,
+}
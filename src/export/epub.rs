@@ -4,10 +4,32 @@ use epub_builder::EpubBuilder;
 use epub_builder::EpubContent;
 use epub_builder::ReferenceType;
 // use epub_builder::Result;
-// use epub_builder::TocElement;
+use epub_builder::TocElement;
 use epub_builder::ZipLibrary;
 
-pub fn export(book: &mut Book, config: &Config) -> Result<()> {
+/// Stylesheet for kobo epubs.
+const KOBO_STYLESHEET: &str = "
+div#book-inner { margin-top: 0; margin-bottom: 0; }
+a.noteref { color: inherit; text-decoration: none; }
+ruby rt { font-size: 0.5em; }
+aside.footnote { font-size: 0.9em; }
+.pitch-high { text-decoration: overline; }
+.pitch-drop { border-right: 1px solid; }
+";
+
+/// Stylesheet for other epubs.
+const STYLESHEET: &str = "
+.footnotes { display: none; }
+.pitch-high { text-decoration: overline; }
+.pitch-drop { border-right: 1px solid; }
+";
+
+/// Generate an epub in memory.
+pub fn generate(
+    book: &mut Book,
+    config: &Config,
+    export_as: html::ExportOption,
+) -> Result<Vec<u8>> {
     // Create the builder.
     let mut builder = match EpubBuilder::new(match ZipLibrary::new() {
         Ok(z) => z,
@@ -39,12 +61,19 @@ pub fn export(book: &mut Book, config: &Config) -> Result<()> {
     builder.metadata(
         "title",
         match book.title.as_str() {
-            "" => format!("jdpub-{}", uuid::Uuid::new_v4()),
-            _ => book.title.clone(),
+            "" => "jdpub",
+            _ => &book.title,
         },
     )?;
 
-    builder.stylesheet(".footnotes { display: hidden; }".as_bytes())?;
+    // Keep generated metadata stable so the same input produces the same epub.
+    builder.set_uuid(book_uuid(book));
+    builder.set_modified_date(build_date());
+
+    match export_as {
+        html::ExportOption::Kepub => builder.stylesheet(KOBO_STYLESHEET.as_bytes())?,
+        _ => builder.stylesheet(STYLESHEET.as_bytes())?,
+    };
     // .stylesheet(css_file.as_bytes())?
     // .add_content(
     //     EpubContent::new("cover.xhtml", dummy_content.as_bytes())
@@ -76,7 +105,9 @@ pub fn export(book: &mut Book, config: &Config) -> Result<()> {
     )?;
 
     // Add the table of contents.
-    builder.inline_toc();
+    if config.export.toc.inline() {
+        builder.inline_toc();
+    }
 
     // Add the xhtml, mark it as beginning of the "real content"
     for (i, chapter) in book.chapters.iter_mut().enumerate() {
@@ -85,7 +116,11 @@ pub fn export(book: &mut Book, config: &Config) -> Result<()> {
             Some(title) => title.clone(),
             None => format!("Chapter {}", i + 1),
         };
-        let mut doc = html::HtmlDoc::new(chapter.ast.clone());
+        let mut doc = html::HtmlDoc::new(chapter.ast.clone(), i);
+        doc.export_as = export_as;
+        if config.export.pitch_accent.unwrap_or_default() {
+            doc.pitch = Some(chapter.pitch.clone());
+        }
 
         // Build to html string.
         let as_html = doc
@@ -97,35 +132,111 @@ pub fn export(book: &mut Book, config: &Config) -> Result<()> {
             .replace(
                 "xml:lang=\"en\"",
                 "xml:lang=\"en\" xmlns:epub=\"http://www.idpf.org/2007/ops\"",
+            )
+            // Link the stylesheet, e.g. for pitch accent marks.
+            .replace(
+                "</head>",
+                "<link href=\"stylesheet.css\" rel=\"stylesheet\" type=\"text/css\"/></head>",
             );
 
         // Add content to epub.
-        builder.add_content(
-            EpubContent::new(
-                format!("{}.xhtml", chapter_name.to_lowercase().replace(" ", "_")),
-                as_html.as_bytes(),
-            )
-            .title(chapter_name)
-            .reftype(ReferenceType::Text),
-        )?;
+        let file_name = format!("chapter_{:04}.xhtml", i + 1);
+        let mut content = EpubContent::new(file_name.as_str(), as_html.as_bytes())
+            .title(chapter_name.as_str())
+            .reftype(ReferenceType::Text);
+        add_headings(
+            &mut content.toc,
+            &file_name,
+            &chapter_name,
+            &doc.headings,
+            config.export.toc.depth(),
+        );
+        builder.add_content(content)?;
     }
 
-    let mut out = std::fs::File::create(&config.export.output_file)?;
-    match builder.generate(&mut out) {
-        Ok(()) => {
-            tracing::info!(
-                "Successfully generated {}.",
-                config.export.output_file.to_string_lossy()
-            );
-        }
-        Err(e) => {
-            bail!(
-                "Failed to write {}: {}",
-                config.export.output_file.to_string_lossy(),
-                e
-            );
-        }
+    let mut generated = Vec::new();
+    if let Err(e) = builder.generate(&mut generated) {
+        bail!("Failed to generate epub: {e}");
+    }
+
+    normalize_zip(generated)
+}
+
+/// Nest chapter headings below the chapter's table of contents entry.
+fn add_headings(
+    toc: &mut TocElement,
+    file_name: &str,
+    chapter_name: &str,
+    headings: &[html::Heading],
+    depth: usize,
+) {
+    // Skip headings that only repeat the chapter title.
+    let headings: Vec<&html::Heading> = headings
+        .iter()
+        .filter(|heading| !heading.title.is_empty() && heading.title != chapter_name)
+        .collect();
+
+    // Heading levels are relative to the largest heading that is kept.
+    let Some(top) = headings.iter().map(|h| h.level).min() else {
+        return;
     };
 
-    Ok(())
+    for heading in headings {
+        // Chapters are depth 1, so the largest heading is depth 2.
+        let level = (heading.level - top) as usize + 2;
+        if level > depth {
+            continue;
+        }
+
+        toc.add(
+            TocElement::new(format!("{file_name}#{}", heading.id), heading.title.as_str())
+                .level(level as i32),
+        );
+    }
+}
+
+/// Get a uuid that is stable for the same book.
+fn book_uuid(book: &Book) -> uuid::Uuid {
+    let mut name = format!("{}\n{}", book.title, book.author);
+    for chapter in book.chapters.iter() {
+        name += "\n";
+        name += chapter.title.as_deref().unwrap_or_default();
+    }
+    uuid::Uuid::new_v5(&uuid::Uuid::NAMESPACE_OID, name.as_bytes())
+}
+
+/// Get the date used for generated files. This respects `SOURCE_DATE_EPOCH`
+/// and otherwise uses a fixed date.
+fn build_date() -> chrono::DateTime<chrono::Utc> {
+    std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.trim().parse::<i64>().ok())
+        .and_then(|epoch| chrono::DateTime::from_timestamp(epoch, 0))
+        .unwrap_or(chrono::DateTime::UNIX_EPOCH)
+}
+
+/// Rewrite zip entries with a fixed modification time. The epub builder
+/// timestamps every entry with the current time.
+fn normalize_zip(generated: Vec<u8>) -> Result<Vec<u8>> {
+    use chrono::{Datelike, Timelike};
+
+    let date = build_date();
+    let modified = zip::DateTime::from_date_and_time(
+        date.year().clamp(1980, 2107) as u16,
+        date.month() as u8,
+        date.day() as u8,
+        date.hour() as u8,
+        date.minute() as u8,
+        date.second() as u8,
+    )
+    .unwrap_or_default();
+
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(generated))?;
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i)?;
+        writer.raw_copy_file_touch(file, modified, None)?;
+    }
+
+    Ok(writer.finish()?.into_inner())
 }
@@ -1,147 +1,270 @@
-use std::io::Write;
-
 use super::*;
 
 pub use build_html::HtmlContainer;
 pub use build_html::{self as html, Html};
 
-pub fn export(book: &mut Book, config: &Config) -> Result<()> {
+/// Furigana level options for the reader, as (value, label).
+const FURIGANA_LEVELS: &[(&str, &str)] = &[
+    ("all", "All furigana"),
+    ("5", "N5 and harder"),
+    ("4", "N4 and harder"),
+    ("3", "N3 and harder"),
+    ("2", "N2 and harder"),
+    ("1", "N1 and harder"),
+    ("none", "No furigana"),
+];
+
+/// Generate the book as a single, self-contained html reader.
+pub fn generate(book: &mut Book, config: &Config) -> Result<String> {
+    let title = match book.title.as_str() {
+        "" => "JDPUB".to_string(),
+        _ => html::escape_html(&book.title),
+    };
+
     // Create new HTML page.
-    let mut page = html::HtmlPage::new().with_title(match book.title.as_str() {
-        "" => "JDPUB",
-        _ => &book.title,
-    });
-
-    // Add the html for each chapter.
-    let mut docs = Vec::new();
-    for (_i, chapter) in book.chapters.iter_mut().enumerate() {
+    let mut page = html::HtmlPage::new()
+        .with_title(&title)
+        .with_meta(vec![("charset", "utf-8")])
+        .with_meta(vec![
+            ("name", "viewport"),
+            ("content", "width=device-width, initial-scale=1"),
+        ]);
+
+    // Add the html for each chapter, along with its sidebar entry.
+    let mut sidebar = html::HtmlElement::new(html::HtmlTag::UnorderedList);
+    let mut content = html::HtmlElement::new(html::HtmlTag::Main).with_attribute("id", "content");
+    for (i, chapter) in book.chapters.iter_mut().enumerate() {
         // Convert chapter to html.
-        // let chapter_name = format!("Chapter {}", i + 1);
-        let mut doc = HtmlDoc::new(chapter.ast.clone());
+        let chapter_name = match &chapter.title {
+            Some(title) => title.clone(),
+            None => format!("Chapter {}", i + 1),
+        };
+        let chapter_id = format!("chapter-{}", i + 1);
+        let mut doc = HtmlDoc::new(chapter.ast.clone(), i);
         doc.export_as = ExportOption::Html;
+        if config.export.pitch_accent.unwrap_or_default() {
+            doc.pitch = Some(chapter.pitch.clone());
+        }
 
         // Add ast as element.
         let root = doc.ast.root.clone();
-        page.add_raw(match doc.to_html_element(&root) {
-            Some(e) => e.to_string(),
-            None => String::new(),
-        });
-
-        docs.push(doc);
-    }
+        let mut section = html::HtmlElement::new(html::HtmlTag::Section)
+            .with_attribute("id", chapter_id.as_str())
+            .with_attribute("class", "chapter");
+        if let Some(elem) = doc.to_html_element(&root) {
+            section.add_child(elem.into());
+        }
 
-    // Add the tooltips (footnotes) for each chapter.
-    let mut footnotes_elem = html::HtmlElement::new(html::HtmlTag::ParagraphText)
-        .with_attribute("class", "footnotes NoShow");
-    for (_i, doc) in docs.iter_mut().enumerate() {
-        for footnote in doc.footnotes.iter() {
-            let footnote = footnote.clone();
+        // Definitions are kept with their chapter and shown as popups.
+        let mut footnotes_elem = html::HtmlElement::new(html::HtmlTag::Div)
+            .with_attribute("class", "footnotes");
+        for footnote in doc.footnotes.drain(..) {
             footnotes_elem.add_child(footnote.into());
         }
-    }
-    page.add_raw(footnotes_elem.to_string());
+        if !footnotes_elem.children.is_empty() {
+            section.add_child(footnotes_elem.into());
+        }
+        content.add_child(section.into());
 
-    // Build to html string.
-    let as_html = page
-        .with_style(
-            "
-            [role=\"tooltip\"] {
-              visibility: hidden;
-              position: absolute;
-              top: 2rem;
-              left: 2rem;
-              background: black;
-              color: white;
-              padding: 0.5rem;
-              border-radius: 0.25rem;
-              /* Give some time before hiding so mouse can exit the input
-              and enter the tooltip */
-              transition: visibility 0.5s;
-            }
-            [aria-describedby]:hover,
-            [aria-describedby]:focus {
-              position: relative;
-            }
-            [aria-describedby]:hover + [role=\"tooltip\"],
-            [aria-describedby]:focus + [role=\"tooltip\"],
-            [role=\"tooltip\"]:hover,
-            [role=\"tooltip\"]:focus {
-              visibility: visible;
-            }
-            ",
-        )
-        .to_html_string()
-        // TODO: This can be removed.
-        .replace(JPDB_FILE_TEMPLATE, "test.xhtml")
-        // We need to add the epub namespace to use epub attributes.
-        .replace(
-            "xml:lang=\"en\"",
-            "xml:lang=\"en\" xmlns:epub=\"http://www.idpf.org/2007/ops\"",
+        // Add the chapter and its headings to the sidebar.
+        let mut entry = html::HtmlElement::new(html::HtmlTag::ListElement).with_child(
+            html::HtmlElement::new(html::HtmlTag::Link)
+                .with_attribute("href", format!("#{chapter_id}"))
+                .with_child(html::escape_html(&chapter_name).into())
+                .into(),
         );
-
-    let mut out = std::fs::File::create(&config.export.output_file)?;
-    match out.write_all(as_html.as_bytes()) {
-        Ok(()) => {
-            tracing::info!(
-                "Successfully generated {}.",
-                config.export.output_file.to_string_lossy()
+        let mut headings = html::HtmlElement::new(html::HtmlTag::UnorderedList);
+        let top = doc.headings.iter().map(|h| h.level).min().unwrap_or_default();
+        for heading in doc.headings.iter() {
+            if heading.level != top || heading.title.is_empty() || heading.title == chapter_name {
+                continue;
+            }
+            headings.add_child(
+                html::HtmlElement::new(html::HtmlTag::ListElement)
+                    .with_child(
+                        html::HtmlElement::new(html::HtmlTag::Link)
+                            .with_attribute("href", format!("#{}", heading.id))
+                            .with_child(html::escape_html(&heading.title).into())
+                            .into(),
+                    )
+                    .into(),
             );
         }
-        Err(e) => {
-            bail!(
-                "Failed to write {}: {}",
-                config.export.output_file.to_string_lossy(),
-                e
-            );
+        if !headings.children.is_empty() {
+            entry.add_child(headings.into());
         }
-    };
+        sidebar.add_child(entry.into());
+    }
+
+    // Add the toolbar and the reader controls.
+    let mut levels = String::new();
+    for (value, label) in FURIGANA_LEVELS {
+        levels += &format!("<option value=\"{value}\">{label}</option>");
+    }
+    page.add_raw(format!(
+        "<header id=\"toolbar\">\
+        <button id=\"toggle-sidebar\" type=\"button\" title=\"Chapters\">&#9776;</button>\
+        <span class=\"title\">{title}</span>\
+        <select id=\"furigana-level\" title=\"Furigana\">{levels}</select>\
+        <button id=\"toggle-dark\" type=\"button\" title=\"Dark mode\">&#9680;</button>\
+        </header>"
+    ));
+    page.add_raw(
+        html::HtmlElement::new(html::HtmlTag::Navigation)
+            .with_attribute("id", "sidebar")
+            .with_child(sidebar.into())
+            .to_html_string(),
+    );
+    page.add_raw(content.to_html_string());
+    page.add_raw("<div id=\"popup\" role=\"tooltip\" hidden=\"hidden\"></div>");
 
-    Ok(())
+    // Styles and scripts are inlined so the reader works offline. The script
+    // runs after the content is loaded.
+    page.add_raw(format!(
+        "<script>{}</script>",
+        read_embedded_text::<ExportMetadata>("reader.js")?
+    ));
+
+    // Build to html string.
+    Ok(page
+        .with_style(read_embedded_text::<ExportMetadata>("reader.css")?)
+        .to_html_string()
+        // TODO: This can be removed.
+        .replace(JPDB_FILE_TEMPLATE, "test.xhtml"))
 }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum ExportOption {
     Html,
     Epub,
+    /// Kobo epub, with sentences wrapped in kobo spans.
+    Kepub,
+}
+
+/// Mark the high morae of a reading with overlines, ending at the downstep.
+fn pitch_markup(reading: &str, downstep: u8) -> Option<String> {
+    let morae = morae(reading);
+    let downstep = downstep as usize;
+    if morae.is_empty() || downstep > morae.len() {
+        return None;
+    }
+
+    let mut markup = String::new();
+    for (i, mora) in morae.iter().enumerate() {
+        // Only the first mora is high in atamadaka words, otherwise the first
+        // mora is low and the pitch stays high until the downstep.
+        let high = match downstep {
+            0 => i > 0,
+            1 => i == 0,
+            _ => i > 0 && i < downstep,
+        };
+        match (high, i + 1 == downstep) {
+            (true, true) => markup += &format!("<span class=\"pitch-high pitch-drop\">{mora}</span>"),
+            (true, false) => markup += &format!("<span class=\"pitch-high\">{mora}</span>"),
+            (false, _) => markup += mora,
+        }
+    }
+    Some(markup)
 }
 
+/// Characters that end a sentence, for kobo spans.
+const SENTENCE_ENDINGS: &[char] = &['。', '！', '？', '!', '?', '.', '…'];
+
+/// Characters that may trail a sentence ending.
+const SENTENCE_CLOSINGS: &[char] = &['」', '』', '）', ')', '"', '\'', '”', '’'];
+
 pub struct HtmlDoc {
     pub ast: durf::Ast,
+    /// Headings found while converting the document, in document order.
+    pub headings: Vec<Heading>,
+    /// Annotated words found while converting the document, in document order.
+    pub noterefs: Vec<NoteRef>,
     footnotes: Vec<html::HtmlElement>,
-    export_as: ExportOption,
+    pub export_as: ExportOption,
+    /// Pitch accents to mark on readings with overlines, if enabled.
+    pub pitch: Option<AnnotatedPitch>,
+    /// Chapter index, used to keep element ids unique and stable.
+    chapter: usize,
+    num_footnotes: usize,
+    kobo_paragraph: usize,
+    kobo_segment: usize,
+}
+
+/// An annotated word that links to its definition.
+pub struct NoteRef {
+    /// Word as it appears in the text.
+    pub word: String,
+    /// Definition of the word.
+    pub tooltip: String,
+    /// Element id of the word.
+    pub id: String,
+}
+
+/// A heading that can be linked to from a table of contents.
+pub struct Heading {
+    /// Heading level, 1 through 6.
+    pub level: u8,
+    /// Plain heading text.
+    pub title: String,
+    /// Element id of the heading.
+    pub id: String,
 }
 
 impl HtmlDoc {
-    pub fn new(ast: durf_parser::Ast) -> Self {
+    pub fn new(ast: durf_parser::Ast, chapter: usize) -> Self {
         Self {
             ast,
+            headings: Vec::new(),
+            noterefs: Vec::new(),
             footnotes: Vec::new(),
             export_as: ExportOption::Epub,
+            pitch: None,
+            chapter,
+            num_footnotes: 0,
+            kobo_paragraph: 0,
+            kobo_segment: 0,
             // page: html::HtmlPage::new().with_title("JPDB"),
-            // num_footnotes: 0,
         }
     }
 
     pub fn build(&mut self) -> Result<html::HtmlPage> {
         // Create new page.
         let mut page = html::HtmlPage::new().with_title("JPDB");
+        page.add_raw(self.build_content()?);
 
+        Ok(page)
+    }
+
+    /// Build the document body, followed by its footnotes.
+    pub fn build_content(&mut self) -> Result<String> {
         // Add ast as element.
         let root = self.ast.root.clone();
-        page.add_raw(match self.to_html_element(&root) {
+        let mut content = match self.to_html_element(&root) {
             Some(e) => e.to_string(),
             None => String::new(),
-        });
+        };
 
         // Add footnotes.
-        let mut footnotes_elem = html::HtmlElement::new(html::HtmlTag::ParagraphText)
-            .with_attribute("class", "footnotes NoShow");
+        let mut footnotes_elem = html::HtmlElement::new(match self.export_as {
+            // Asides are not valid inside of paragraphs for kobo's renderer.
+            ExportOption::Kepub => html::HtmlTag::Div,
+            _ => html::HtmlTag::ParagraphText,
+        })
+        .with_attribute("class", "footnotes NoShow");
         let footnotes: Vec<html::HtmlElement> = self.footnotes.drain(..).collect();
         for footnote in footnotes {
             footnotes_elem = footnotes_elem.with_child(footnote.into());
         }
-        page.add_raw(footnotes_elem.to_string());
+        content += footnotes_elem.to_string().as_str();
 
-        Ok(page)
+        // Kobo expects the book content to be wrapped in these containers.
+        if self.export_as == ExportOption::Kepub {
+            content = format!(
+                "<div id=\"book-columns\"><div id=\"book-inner\">{content}</div></div>"
+            );
+        }
+
+        Ok(content)
     }
 
     fn to_html_element(&mut self, node: &durf::Node) -> Option<html::HtmlElement> {
@@ -163,44 +286,75 @@ impl HtmlDoc {
                 Some(elem)
             }
             durf_parser::RawNode::Text(text) => {
-                let mut elem = html::HtmlElement::new(html::HtmlTag::Div);
+                // Whole headings are rendered as a single, linkable element.
+                let text_heading = text_heading(text);
+                let mut elem = match text_heading {
+                    Some(level) => {
+                        let id =
+                            format!("heading-{}-{}", self.chapter + 1, self.headings.len() + 1);
+                        self.headings.push(Heading {
+                            level,
+                            title: text.collect().trim().to_string(),
+                            id: id.clone(),
+                        });
+                        html::HtmlElement::new(heading_tag(level)).with_attribute("id", id)
+                    }
+                    None => html::HtmlElement::new(html::HtmlTag::Div),
+                };
+                self.kobo_paragraph += 1;
+                self.kobo_segment = 0;
+                let mut plain = String::new();
                 for fragment in text.fragments.iter() {
-                    // If plain text, just add.
-                    if fragment.attributes.is_plain() {
-                        elem.add_child(fragment.text.as_str().into());
+                    let mut attributes = fragment.attributes.clone();
+                    if text_heading.is_some() {
+                        attributes.heading = None;
+                    }
+
+                    // If plain text, just add. Runs of plain text are joined so
+                    // they can be split by sentence.
+                    if attributes.is_plain() && attributes.annotation.is_none() {
+                        plain += fragment.text.as_str();
                         continue;
                     }
+                    if !plain.is_empty() {
+                        elem.add_child(self.text_child(&plain));
+                        plain.clear();
+                    }
 
                     // Heading is special.
-                    if let Some(heading) = &fragment.attributes.heading {
+                    if let Some(heading) = &attributes.heading {
                         elem = elem.with_child(
-                            html::HtmlElement::new(match heading {
-                                0..2 => html::HtmlTag::Heading1,
-                                2 => html::HtmlTag::Heading2,
-                                3 => html::HtmlTag::Heading3,
-                                4 => html::HtmlTag::Heading4,
-                                5 => html::HtmlTag::Heading5,
-                                _ => html::HtmlTag::Heading6,
-                            })
-                            .with_child(fragment.text.as_str().into())
-                            .into(),
+                            html::HtmlElement::new(heading_tag(*heading))
+                                .with_child(html::escape_html(&fragment.text).into())
+                                .into(),
                         );
                         continue;
                     }
 
                     let mut text_elem = html::HtmlElement::new(html::HtmlTag::Span);
                     if let Some(tooltip) = &fragment.attributes.tooltip {
-                        let id = uuid::Uuid::new_v4();
+                        // Ids are derived from the position in the book so
+                        // repeated builds produce identical output.
+                        self.num_footnotes += 1;
+                        let id = format!("{}-{}", self.chapter + 1, self.num_footnotes);
+                        let mut link = html::HtmlElement::new(html::HtmlTag::Link);
+                        if self.export_as == ExportOption::Html {
+                            if let Some(level) = tooltip_level(tooltip) {
+                                link.add_attribute("data-jlpt", level as i8);
+                            }
+                        }
                         text_elem = text_elem.with_child(
-                            html::HtmlElement::new(html::HtmlTag::Link)
+                            link
                                 .with_child(match &fragment.attributes.annotation {
-                                    Some(annotation) => html::HtmlChild::Raw(format!(
+                                    Some(annotation) => self.raw_child(format!(
                                         "<ruby>{}<rp>(</rp><rt>{}</rt><rp>)</rp></ruby>",
-                                        fragment.text, annotation
+                                        html::escape_html(&fragment.text),
+                                        self.ruby_text(&fragment.text, annotation)
                                     )),
-                                    None => fragment.text.as_str().into(),
+                                    None => self.text_child(&fragment.text),
                                 })
                                 .with_attribute("class", "noteref")
+                                .with_attribute("id", format!("noteref-{id}"))
                                 .with_attribute(
                                     "href",
                                     // format!("{JPDB_FILE_TEMPLATE}#tooltip-{id}"),
@@ -212,9 +366,14 @@ impl HtmlDoc {
                                 .into(),
                         );
                         // .with_child(html::HtmlChild::new(html::HtmlTag::))
+                        self.noterefs.push(NoteRef {
+                            word: fragment.text.trim().to_string(),
+                            tooltip: tooltip.clone(),
+                            id: format!("noteref-{id}"),
+                        });
                         self.footnotes.push(
                             html::HtmlElement::new(html::HtmlTag::Aside)
-                                .with_child(tooltip.as_str().into())
+                                .with_child(html::escape_html(tooltip).into())
                                 .with_attribute("class", "footnote")
                                 .with_attribute("id", format!("tooltip-{id}"))
                                 .with_attribute("epub:type", "footnote")
@@ -224,7 +383,7 @@ impl HtmlDoc {
                                     "role",
                                     match self.export_as {
                                         ExportOption::Html => "tooltip",
-                                        ExportOption::Epub => "doc-footnote",
+                                        ExportOption::Epub | ExportOption::Kepub => "doc-footnote",
                                     },
                                 )
                                 .into(),
@@ -235,12 +394,92 @@ impl HtmlDoc {
                     // if fragment.attributes.preformatted {
                     //     text_elem = text_elem.
                     // }
-                    elem =
-                        elem.with_child(text_elem.with_child(fragment.text.as_str().into()).into());
+                    let child = match &fragment.attributes.annotation {
+                        Some(annotation) => self.raw_child(format!(
+                            "<ruby>{}<rp>(</rp><rt>{}</rt><rp>)</rp></ruby>",
+                            html::escape_html(&fragment.text),
+                            self.ruby_text(&fragment.text, annotation)
+                        )),
+                        None => self.text_child(&fragment.text),
+                    };
+                    elem = elem.with_child(text_elem.with_child(child).into());
+                }
+                if !plain.is_empty() {
+                    elem.add_child(self.text_child(&plain));
                 }
 
                 Some(elem)
             }
         }
     }
+
+    /// Format ruby text, marking the pitch accent of the word if enabled.
+    fn ruby_text(&self, word: &str, annotation: &str) -> String {
+        match self
+            .pitch
+            .as_ref()
+            .and_then(|pitch| pitch.get(word, annotation))
+            .and_then(|downstep| pitch_markup(annotation, downstep))
+        {
+            Some(markup) => markup,
+            None => html::escape_html(annotation),
+        }
+    }
+
+    /// Create a text child, wrapped in kobo spans when needed.
+    fn text_child(&mut self, text: &str) -> html::HtmlChild {
+        if self.export_as != ExportOption::Kepub || text.trim().is_empty() {
+            return html::escape_html(text).into();
+        }
+
+        let mut spans = String::new();
+        let mut sentence = String::new();
+        let mut ended = false;
+        for c in text.chars() {
+            // Closing quotes stay with the sentence they end.
+            if ended && !SENTENCE_CLOSINGS.contains(&c) {
+                spans += &self.kobo_span(&html::escape_html(&sentence));
+                sentence.clear();
+                ended = false;
+            }
+            sentence.push(c);
+            if SENTENCE_ENDINGS.contains(&c) {
+                ended = true;
+            }
+        }
+        if !sentence.is_empty() {
+            spans += &self.kobo_span(&html::escape_html(&sentence));
+        }
+
+        html::HtmlChild::Raw(spans)
+    }
+
+    /// Create a raw html child, wrapped in a kobo span when needed.
+    fn raw_child(&mut self, raw: String) -> html::HtmlChild {
+        match self.export_as {
+            ExportOption::Kepub => html::HtmlChild::Raw(self.kobo_span(&raw)),
+            _ => html::HtmlChild::Raw(raw),
+        }
+    }
+
+    /// Wrap content in the next kobo span.
+    fn kobo_span(&mut self, content: &str) -> String {
+        self.kobo_segment += 1;
+        format!(
+            "<span class=\"koboSpan\" id=\"kobo.{}.{}\">{content}</span>",
+            self.kobo_paragraph, self.kobo_segment
+        )
+    }
+}
+
+/// Get the html tag for a heading level.
+fn heading_tag(level: u8) -> html::HtmlTag {
+    match level {
+        0..2 => html::HtmlTag::Heading1,
+        2 => html::HtmlTag::Heading2,
+        3 => html::HtmlTag::Heading3,
+        4 => html::HtmlTag::Heading4,
+        5 => html::HtmlTag::Heading5,
+        _ => html::HtmlTag::Heading6,
+    }
 }
@@ -1,5 +1,7 @@
 mod epub;
 mod html;
+mod site;
+mod text;
 
 use super::*;
 
@@ -7,10 +9,57 @@ use html::*;
 
 const JPDB_FILE_TEMPLATE: &'static str = "{{JPDB_FILE_TEMPLATE}}";
 
+/// Embedded export assets.
+#[derive(RustEmbed)]
+#[folder = "metadata/export"]
+struct ExportMetadata;
+
 /// Export chapters according to the config.
 pub fn export(book: &mut Book, config: &Config) -> Result<()> {
-    match config.export.export_type() {
-        config::ExportType::Epub => epub::export(book, config),
-        config::ExportType::Html => html::export(book, config),
+    // Sites are written as a directory of files.
+    if config.export.export_type() == config::ExportType::Site {
+        return site::export(book, config);
     }
+
+    let generated = generate(book, config)?;
+    match std::fs::write(&config.export.output_file, generated) {
+        Ok(()) => {
+            tracing::info!(
+                "Successfully generated {}.",
+                config.export.output_file.to_string_lossy()
+            );
+        }
+        Err(e) => {
+            bail!(
+                "Failed to write {}: {}",
+                config.export.output_file.to_string_lossy(),
+                e
+            );
+        }
+    };
+
+    Ok(())
+}
+
+/// Generate the export in memory, according to the config.
+pub fn generate(book: &mut Book, config: &Config) -> Result<Vec<u8>> {
+    Ok(match config.export.export_type() {
+        config::ExportType::Epub => epub::generate(book, config, ExportOption::Epub)?,
+        config::ExportType::Kepub => epub::generate(book, config, ExportOption::Kepub)?,
+        config::ExportType::Html => html::generate(book, config)?.into_bytes(),
+        config::ExportType::Site => bail!("Sites can only be exported to a directory."),
+        config::ExportType::Text => {
+            text::generate(book, config, text::TextFormat::Plain).into_bytes()
+        }
+        config::ExportType::Markdown => {
+            text::generate(book, config, text::TextFormat::Markdown).into_bytes()
+        }
+    })
+}
+
+/// Render an annotated ast as an html fragment, matching the html export.
+pub fn render_html(ast: durf::Ast) -> Result<String> {
+    let mut doc = HtmlDoc::new(ast, 0);
+    doc.export_as = ExportOption::Html;
+    doc.build_content()
 }
@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+
+use super::*;
+
+use build_html::{self as html, Html, HtmlContainer};
+
+/// Export the book as a static html site: an index page, one page per chapter,
+/// a vocabulary page and a shared stylesheet.
+pub fn export(book: &mut Book, config: &Config) -> Result<()> {
+    let dir = &config.export.output_file;
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        bail!("Failed to create {}: {}", dir.to_string_lossy(), e);
+    }
+
+    let title = match book.title.as_str() {
+        "" => "JDPUB",
+        _ => &book.title,
+    };
+
+    // Write the shared stylesheet.
+    write_file(
+        &dir.join("style.css"),
+        read_embedded_text::<ExportMetadata>("site.css")?,
+    )?;
+
+    // Write each chapter, remembering where each word appears.
+    let chapter_names: Vec<String> = book
+        .chapters
+        .iter()
+        .enumerate()
+        .map(|(i, chapter)| match &chapter.title {
+            Some(title) => title.clone(),
+            None => format!("Chapter {}", i + 1),
+        })
+        .collect();
+    let mut vocabulary: BTreeMap<String, VocabEntry> = BTreeMap::new();
+    for (i, chapter) in book.chapters.iter().enumerate() {
+        let mut doc = HtmlDoc::new(chapter.ast.clone(), i);
+        doc.export_as = ExportOption::Html;
+        if config.export.pitch_accent.unwrap_or_default() {
+            doc.pitch = Some(chapter.pitch.clone());
+        }
+        let content = doc.build_content()?;
+
+        for noteref in doc.noterefs.drain(..) {
+            let entry = vocabulary
+                .entry(noteref.word)
+                .or_insert_with(|| VocabEntry {
+                    tooltip: noteref.tooltip,
+                    appearances: Vec::new(),
+                });
+            match entry.appearances.last_mut() {
+                Some((chapter, _, count)) if *chapter == i => *count += 1,
+                _ => entry.appearances.push((i, noteref.id, 1)),
+            }
+        }
+
+        let nav = chapter_nav(i, chapter_names.len());
+        let page = site_page(&chapter_names[i], title)
+            .with_raw(nav.as_str())
+            .with_raw(content)
+            .with_raw(nav.as_str());
+        write_file(&dir.join(chapter_file_name(i)), page.to_html_string())?;
+    }
+
+    // Write the index.
+    let mut chapters = html::HtmlElement::new(html::HtmlTag::OrderedList);
+    for (i, chapter_name) in chapter_names.iter().enumerate() {
+        chapters.add_child(
+            html::HtmlElement::new(html::HtmlTag::ListElement)
+                .with_child(link(chapter_file_name(i), chapter_name).into())
+                .into(),
+        );
+    }
+    let mut index = site_page(title, title)
+        .with_raw(
+            html::HtmlElement::new(html::HtmlTag::Heading1)
+                .with_child(html::escape_html(title).into())
+                .to_html_string(),
+        )
+        .with_raw(chapters.to_html_string());
+    if !vocabulary.is_empty() {
+        index.add_raw(
+            html::HtmlElement::new(html::HtmlTag::ParagraphText)
+                .with_child(link("vocabulary.html".into(), "Vocabulary").into())
+                .to_html_string(),
+        );
+    }
+    write_file(&dir.join("index.html"), index.to_html_string())?;
+
+    // Write the vocabulary index.
+    let mut table = html::HtmlElement::new(html::HtmlTag::Table)
+        .with_attribute("class", "vocabulary")
+        .with_raw("<tr><th>Word</th><th>Definition</th><th>Appears in</th></tr>");
+    for (word, entry) in vocabulary.iter() {
+        let mut appearances = Vec::new();
+        for (chapter, id, count) in entry.appearances.iter() {
+            let label = match count {
+                1 => chapter_names[*chapter].clone(),
+                _ => format!("{} (×{count})", chapter_names[*chapter]),
+            };
+            appearances.push(
+                link(format!("{}#{id}", chapter_file_name(*chapter)), &label).to_html_string(),
+            );
+        }
+        table.add_child(
+            html::HtmlElement::new(html::HtmlTag::TableRow)
+                .with_child(
+                    html::HtmlElement::new(html::HtmlTag::TableCell)
+                        .with_child(html::escape_html(word).into())
+                        .into(),
+                )
+                .with_child(
+                    html::HtmlElement::new(html::HtmlTag::TableCell)
+                        .with_child(html::escape_html(&entry.tooltip).into())
+                        .into(),
+                )
+                .with_child(
+                    html::HtmlElement::new(html::HtmlTag::TableCell)
+                        .with_child(html::HtmlChild::Raw(appearances.join(", ")))
+                        .into(),
+                )
+                .into(),
+        );
+    }
+    let vocabulary_page = site_page("Vocabulary", title)
+        .with_raw(format!(
+            "<nav class=\"chapter-nav\">{}</nav>",
+            link("index.html".into(), title).to_html_string()
+        ))
+        .with_raw(
+            html::HtmlElement::new(html::HtmlTag::Heading1)
+                .with_child("Vocabulary".into())
+                .to_html_string(),
+        )
+        .with_raw(table.to_html_string());
+    write_file(
+        &dir.join("vocabulary.html"),
+        vocabulary_page.to_html_string(),
+    )?;
+
+    tracing::info!(
+        "Successfully generated {} with {} chapters.",
+        dir.to_string_lossy(),
+        chapter_names.len()
+    );
+
+    Ok(())
+}
+
+/// A word in the vocabulary index.
+struct VocabEntry {
+    tooltip: String,
+    /// Chapter index, first element id and count of each appearance.
+    appearances: Vec<(usize, String, usize)>,
+}
+
+/// Get the file name for a chapter page.
+fn chapter_file_name(index: usize) -> String {
+    format!("chapter_{:04}.html", index + 1)
+}
+
+/// Create a page using the shared stylesheet.
+fn site_page(page_title: &str, book_title: &str) -> html::HtmlPage {
+    let title = match page_title == book_title {
+        true => book_title.to_string(),
+        false => format!("{page_title} - {book_title}"),
+    };
+    html::HtmlPage::new()
+        .with_title(html::escape_html(&title))
+        .with_meta(vec![("charset", "utf-8")])
+        .with_meta(vec![
+            ("name", "viewport"),
+            ("content", "width=device-width, initial-scale=1"),
+        ])
+        .with_stylesheet("style.css")
+}
+
+/// Create the previous/contents/next navigation for a chapter.
+fn chapter_nav(index: usize, count: usize) -> String {
+    let previous = match index {
+        0 => String::from("<span></span>"),
+        _ => link(chapter_file_name(index - 1), "← Previous").to_html_string(),
+    };
+    let next = match index + 1 < count {
+        true => link(chapter_file_name(index + 1), "Next →").to_html_string(),
+        false => String::from("<span></span>"),
+    };
+    format!(
+        "<nav class=\"chapter-nav\">{previous}{}{next}</nav>",
+        link("index.html".into(), "Contents").to_html_string()
+    )
+}
+
+/// Create a link element.
+fn link(href: String, text: &str) -> html::HtmlElement {
+    html::HtmlElement::new(html::HtmlTag::Link)
+        .with_attribute("href", href)
+        .with_child(html::escape_html(text).into())
+}
+
+/// Write a file in the site.
+fn write_file(path: &Path, content: String) -> Result<()> {
+    match std::fs::write(path, content) {
+        Ok(()) => Ok(()),
+        Err(e) => bail!("Failed to write {}: {}", path.to_string_lossy(), e),
+    }
+}
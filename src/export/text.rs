@@ -0,0 +1,200 @@
+use super::*;
+
+/// Text export format.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TextFormat {
+    Plain,
+    Markdown,
+}
+
+/// Generate the book as text.
+pub fn generate(book: &mut Book, config: &Config, format: TextFormat) -> String {
+    let notation = config
+        .export
+        .reading_notation
+        .unwrap_or(match format {
+            TextFormat::Plain => ReadingNotation::Parentheses,
+            TextFormat::Markdown => ReadingNotation::Ruby,
+        });
+
+    let mut out = String::new();
+    if !book.title.is_empty() {
+        out += &heading(format, 1, &escape(format, &book.title));
+        out += "\n\n";
+    }
+
+    // Footnotes are numbered across the whole book, so that their labels
+    // stay unique in a single Markdown file.
+    let mut doc = TextDoc::new(format, notation);
+    for chapter in book.chapters.iter() {
+        if let Some(title) = &chapter.title {
+            out += &heading(format, 2, &escape(format, title));
+            out += "\n\n";
+        }
+        out += doc.build(&chapter.ast).as_str();
+        out += "\n\n";
+    }
+
+    out.trim_end().to_string() + "\n"
+}
+
+/// Chapters converted to text.
+pub struct TextDoc {
+    format: TextFormat,
+    notation: ReadingNotation,
+    footnotes: Vec<String>,
+    /// Number of footnotes written by previous chapters.
+    numbered: usize,
+}
+
+impl TextDoc {
+    pub fn new(format: TextFormat, notation: ReadingNotation) -> Self {
+        Self {
+            format,
+            notation,
+            footnotes: Vec::new(),
+            numbered: 0,
+        }
+    }
+
+    /// Convert an ast to text, with definitions as numbered footnotes at the
+    /// end.
+    pub fn build(&mut self, ast: &durf::Ast) -> String {
+        let mut text = self.node_to_text(&ast.root);
+
+        let footnotes: Vec<String> = self.footnotes.drain(..).collect();
+        if !footnotes.is_empty() {
+            text += "\n\n";
+            if self.format == TextFormat::Plain {
+                text += "---\n";
+            }
+            for (i, footnote) in footnotes.iter().enumerate() {
+                let number = self.numbered + i + 1;
+                text += &match self.format {
+                    TextFormat::Plain => format!("[{number}] {footnote}\n"),
+                    TextFormat::Markdown => format!("[^{number}]: {footnote}\n"),
+                };
+            }
+            self.numbered += footnotes.len();
+        }
+
+        text.trim_end().to_string()
+    }
+
+    fn node_to_text(&mut self, node: &durf::Node) -> String {
+        match &**node {
+            durf_parser::RawNode::Empty => String::new(),
+            durf_parser::RawNode::Section(section) => {
+                let mut children = Vec::new();
+                for (i, node) in section.nodes.iter().enumerate() {
+                    let child = self.node_to_text(node);
+                    if child.trim().is_empty() {
+                        continue;
+                    }
+                    children.push(match section.ordering() {
+                        durf_parser::SectionOrdering::Set => child,
+                        durf_parser::SectionOrdering::List => format!("- {child}"),
+                        durf_parser::SectionOrdering::Enumeration => format!("{}. {child}", i + 1),
+                    });
+                }
+                match section.ordering() {
+                    durf_parser::SectionOrdering::Set => children.join("\n\n"),
+                    _ => children.join("\n"),
+                }
+            }
+            durf_parser::RawNode::Text(text) => {
+                let mut line = String::new();
+                for fragment in text.fragments.iter() {
+                    line += &self.fragment_to_text(fragment);
+                }
+                match text_heading(text) {
+                    Some(level) => heading(self.format, level, line.trim()),
+                    None => line.trim().to_string(),
+                }
+            }
+        }
+    }
+
+    fn fragment_to_text(&mut self, fragment: &durf::TextFragment) -> String {
+        let attributes = &fragment.attributes;
+        // Code spans are shown as is, so they are not escaped.
+        let format = match attributes.preformatted {
+            true => TextFormat::Plain,
+            false => self.format,
+        };
+        let mut text = match &attributes.annotation {
+            Some(annotation) if !fragment.text.trim().is_empty() => {
+                self.reading(&escape(format, &fragment.text), &escape(format, annotation))
+            }
+            _ => escape(format, &fragment.text),
+        };
+
+        if self.format == TextFormat::Markdown {
+            if attributes.preformatted {
+                text = format!("`{text}`");
+            }
+            if attributes.italic {
+                text = format!("*{text}*");
+            }
+            if attributes.bold {
+                text = format!("**{text}**");
+            }
+            if let Some(link) = &attributes.link {
+                text = format!("[{text}]({link})");
+            }
+        }
+
+        if let Some(tooltip) = &attributes.tooltip {
+            self.footnotes.push(escape(self.format, tooltip));
+            let number = self.numbered + self.footnotes.len();
+            text += &match self.format {
+                TextFormat::Plain => format!("[{number}]"),
+                TextFormat::Markdown => format!("[^{number}]"),
+            };
+        }
+
+        text
+    }
+
+    /// Format text with its reading.
+    fn reading(&self, text: &str, reading: &str) -> String {
+        match self.notation {
+            ReadingNotation::Parentheses => format!("{text}({reading})"),
+            ReadingNotation::Aozora => format!("｜{text}《{reading}》"),
+            ReadingNotation::Ruby => {
+                format!("<ruby>{text}<rp>(</rp><rt>{reading}</rt><rp>)</rp></ruby>")
+            }
+        }
+    }
+}
+
+/// Format a heading.
+fn heading(format: TextFormat, level: u8, text: &str) -> String {
+    match format {
+        TextFormat::Plain => text.to_string(),
+        TextFormat::Markdown => format!(
+            "{} {text}",
+            (0..level.clamp(1, 6)).map(|_| "#").collect::<String>()
+        ),
+    }
+}
+
+/// Escape characters that Markdown would read as markup.
+fn escape(format: TextFormat, text: &str) -> String {
+    match format {
+        TextFormat::Plain => text.to_string(),
+        TextFormat::Markdown => {
+            let mut escaped = String::with_capacity(text.len());
+            for c in text.chars() {
+                if matches!(
+                    c,
+                    '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '&' | '#' | '|' | '~'
+                ) {
+                    escaped.push('\\');
+                }
+                escaped.push(c);
+            }
+            escaped
+        }
+    }
+}
@@ -2,13 +2,28 @@ use super::*;
 
 use epub_parser::Epub;
 
+#[cfg(not(target_arch = "wasm32"))]
 pub fn import(
     path: &Path,
     #[allow(unused)] config: &Config,
     book: &mut Book,
-    #[allow(unused)] chapter_config: &ChapterConfig,
+    chapter_config: &ChapterConfig,
 ) -> Result<()> {
-    let epub = match Epub::parse(path) {
+    let data = match std::fs::read(path) {
+        Ok(data) => data,
+        Err(e) => bail!("{e}"),
+    };
+    let first = book.chapters.len();
+    import_bytes(&data, book)?;
+    for chapter in book.chapters[first..].iter_mut() {
+        chapter.language = chapter_config.language;
+    }
+    Ok(())
+}
+
+/// Import an epub from memory.
+pub fn import_bytes(data: &[u8], book: &mut Book) -> Result<()> {
+    let epub = match Epub::parse_from_buffer(data) {
         Ok(epub) => epub,
         Err(e) => bail!("{e:?}"),
     };
@@ -48,6 +63,8 @@ pub fn import(
     let chapter = Chapter {
         title: Some(book.title.clone()),
         ast,
+        language: None,
+        pitch: AnnotatedPitch::default(),
     };
     book.chapters.push(chapter);
 
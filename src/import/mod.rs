@@ -1,11 +1,16 @@
+#[cfg(not(target_arch = "wasm32"))]
 use std::str::FromStr;
 
 use super::*;
 
 mod epub;
 
+#[cfg(feature = "wasm")]
+pub use epub::import_bytes as import_epub_bytes;
+
 /// Parse the input to durf asts.
-pub async fn import(config: &mut Config) -> Result<Book> {
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn import(config: &Config) -> Result<Book> {
     let mut book = config.export.book()?;
 
     for chapter in config.import.chapters.iter() {
@@ -24,16 +29,13 @@ pub async fn import(config: &mut Config) -> Result<Book> {
             let res = client.get(&uri).send().await?;
             let body = res.text().await?;
 
-            let flags = config.parse.html.parse_flags()?;
-            let mut ast = match durf::Ast::from_html(&body, flags) {
-                Ok(ast) => ast,
-                Err(e) => {
-                    tracing::error!("Failed to parse website: {e}");
-                    continue;
+            match parse_html(&body, title, config) {
+                Ok(mut parsed) => {
+                    parsed.language = chapter.language;
+                    book.chapters.push(parsed);
                 }
-            };
-            ast.minimize();
-            book.chapters.push(Chapter { ast, title });
+                Err(e) => tracing::error!("Failed to parse website: {e}"),
+            }
             continue;
         }
 
@@ -75,16 +77,13 @@ pub async fn import(config: &mut Config) -> Result<Book> {
             };
 
             // We parsed the actual document.
-            let flags = config.parse.html.parse_flags()?;
-            let mut ast = match durf::Ast::from_html(&body, flags) {
-                Ok(ast) => ast,
-                Err(e) => {
-                    tracing::error!("Failed to parse website: {e}");
-                    continue;
+            match parse_html(&body, title, config) {
+                Ok(mut parsed) => {
+                    parsed.language = chapter.language;
+                    book.chapters.push(parsed);
                 }
-            };
-            ast.minimize();
-            book.chapters.push(Chapter { ast, title });
+                Err(e) => tracing::error!("Failed to parse website: {e}"),
+            }
             continue;
         }
     }
@@ -96,3 +95,19 @@ pub async fn import(config: &mut Config) -> Result<Book> {
 
     Ok(book)
 }
+
+/// Parse an html document to a chapter.
+pub fn parse_html(body: &str, title: Option<String>, config: &Config) -> Result<Chapter> {
+    let flags = config.parse.html.parse_flags()?;
+    let mut ast = match durf::Ast::from_html(body, flags) {
+        Ok(ast) => ast,
+        Err(e) => bail!("{e}"),
+    };
+    ast.minimize();
+    Ok(Chapter {
+        ast,
+        title,
+        language: None,
+        pitch: AnnotatedPitch::default(),
+    })
+}
@@ -0,0 +1,535 @@
+//! Mandarin Chinese language backend.
+
+use super::*;
+
+/// Chinese backend, using the HSK vocabulary lists and CC-CEDICT.
+///
+/// Text is segmented by bidirectional maximum matching against the loaded
+/// words instead of charabia's jieba segmenter. Words are then only split
+/// where the HSK lists, CC-CEDICT, glossaries and names have an entry to
+/// annotate, and the backend doesn't need jieba's dictionary, e.g. in the
+/// browser. Words missing from them are split into single characters.
+pub struct ChineseBackend {
+    common: HashMap<String, HskVocab>,
+    /// Entries loaded from CC-CEDICT, keyed by both traditional and simplified
+    /// forms.
+    dictionary: HashMap<String, CedictEntry>,
+    /// Where words are looked up, in order.
+    sources: Vec<Source>,
+    /// Configured names, so they are kept as one word.
+    names: Vec<String>,
+    /// Series terms, consulted before any other source.
+    glossary: Glossary,
+    /// Length of the longest known word, in characters.
+    max_len: usize,
+}
+
+impl ChineseBackend {
+    /// Create a new ChineseBackend.
+    pub fn new(config: &Config) -> Result<Self> {
+        let mut backend = Self {
+            common: HashMap::new(),
+            dictionary: HashMap::new(),
+            sources: dictionary::sources(&config.language, Language::Chinese)?,
+            names: config
+                .language
+                .names
+                .iter()
+                .map(|name| name.name.clone())
+                .collect(),
+            glossary: Glossary::new(&config.language)?,
+            max_len: 1,
+        };
+        for word in backend.names.iter().chain(backend.glossary.words()) {
+            backend.max_len = backend.max_len.max(word.chars().count());
+        }
+
+        for path in ChineseMetadata::iter() {
+            let level = match path
+                .strip_prefix("hsk/hsk")
+                .and_then(|name| name.strip_suffix(".csv"))
+                .and_then(|level| level.parse::<u8>().ok())
+            {
+                Some(level) => level,
+                None => continue,
+            };
+            backend.read_hsk_csv(&path, level)?;
+        }
+
+        tracing::debug!("Parsed {} HSK entries.", backend.common.len());
+
+        if let Some(path) = &config.language.chinese.dictionary {
+            let content = match std::fs::read_to_string(path) {
+                Ok(content) => content,
+                Err(e) => bail!(
+                    "Unable to read CC-CEDICT file '{}': {e}",
+                    path.to_string_lossy()
+                ),
+            };
+            backend.load_cedict(&content);
+        }
+
+        Ok(backend)
+    }
+
+    /// Read an hsk csv.
+    fn read_hsk_csv(&mut self, file: &str, level: u8) -> Result<()> {
+        let content = read_embedded_text::<ChineseMetadata>(file)?;
+        let mut reader =
+            csv::ReaderBuilder::new().from_reader(std::io::BufReader::new(content.as_bytes()));
+        for result in reader.deserialize() {
+            let record: HskEntry = match result {
+                Ok(record) => record,
+                Err(e) => {
+                    tracing::warn!("Unable to parse HskEntry: {e}");
+                    continue;
+                }
+            };
+            self.max_len = self.max_len.max(record.word.chars().count());
+            // Keep the easiest level of words listed more than once.
+            let level = HskLevel::from(level);
+            if let Some(existing) = self.common.get(&record.word) {
+                if existing.level <= level {
+                    continue;
+                }
+            }
+            self.common.insert(
+                record.word,
+                HskVocab {
+                    pinyin: record.pinyin,
+                    meaning: record.meaning,
+                    level,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Load a dictionary in the CC-CEDICT format.
+    pub fn load_cedict(&mut self, content: &str) {
+        for line in content.lines() {
+            if line.starts_with('#') {
+                continue;
+            }
+            let Some(entry) = CedictEntry::parse(line) else {
+                continue;
+            };
+            for word in [entry.traditional.clone(), entry.simplified.clone()] {
+                self.max_len = self.max_len.max(word.chars().count());
+                // Prefer common words over proper nouns, which have
+                // capitalized pinyin.
+                let replace = match self.dictionary.get(&word) {
+                    Some(existing) => existing.is_proper() && !entry.is_proper(),
+                    None => true,
+                };
+                if replace {
+                    self.dictionary.insert(word, entry.clone());
+                }
+            }
+        }
+
+        tracing::debug!("Loaded {} CC-CEDICT entries.", self.dictionary.len());
+    }
+
+    /// Split a run of Chinese characters into words, matching the longest
+    /// known words both from the start and from the end of the run. The split
+    /// with fewer words wins, then the one with fewer single characters, and
+    /// otherwise the one matched from the end, which resolves more overlaps,
+    /// e.g. 研究/生命 rather than 研究生/命.
+    fn segment(&self, chars: &[char]) -> Vec<String> {
+        let forward = self.match_words(chars, false);
+        let backward = self.match_words(chars, true);
+        let singles = |words: &[String]| words.iter().filter(|w| w.chars().count() == 1).count();
+        match (forward.len(), singles(&forward)) < (backward.len(), singles(&backward)) {
+            true => forward,
+            false => backward,
+        }
+    }
+
+    /// Split characters by the longest known word at each position, or a
+    /// single character, from the start or the end.
+    fn match_words(&self, chars: &[char], from_end: bool) -> Vec<String> {
+        let mut words = Vec::new();
+        let (mut start, mut end) = (0, chars.len());
+        while start < end {
+            let mut len = 1;
+            for span in (2..=self.max_len.min(end - start)).rev() {
+                let word: String = match from_end {
+                    true => chars[end - span..end].iter().collect(),
+                    false => chars[start..start + span].iter().collect(),
+                };
+                if self.contains(&word) {
+                    len = span;
+                    break;
+                }
+            }
+            match from_end {
+                true => {
+                    words.push(chars[end - len..end].iter().collect());
+                    end -= len;
+                }
+                false => {
+                    words.push(chars[start..start + len].iter().collect());
+                    start += len;
+                }
+            }
+        }
+        if from_end {
+            words.reverse();
+        }
+        words
+    }
+
+    /// Lookup word in the configured sources.
+    fn lookup_sources(&self, word: &str) -> Option<DictLookup> {
+        let mut lookup = self.sources.iter().find_map(|source| match source {
+            Source::Common => self.common.get(word).map(DictLookup::from),
+            Source::Dictionary => self.lookup_dictionary(word),
+            Source::External(dictionary) => {
+                let entry = dictionary.lookup(word)?;
+                Some(DictLookup {
+                    kana: entry.reading,
+                    meaning: entry.meaning,
+                    tags: entry.tags,
+                    source: Some(LookupSource::External),
+                    ..Default::default()
+                })
+            }
+        })?;
+
+        // Other sources may still know the level or frequency of the word.
+        if lookup.hsk.is_none() {
+            lookup.hsk = Some(match self.common.get(word) {
+                Some(common) => common.level,
+                None => HskLevel::None,
+            });
+        }
+        if lookup.frequency.is_none() {
+            lookup.frequency = self.sources.iter().find_map(|source| match source {
+                Source::External(dictionary) => dictionary.frequency(word),
+                _ => None,
+            });
+        }
+
+        Some(lookup)
+    }
+
+    /// Lookup word in CC-CEDICT.
+    fn lookup_dictionary(&self, word: &str) -> Option<DictLookup> {
+        let entry = self.dictionary.get(word)?;
+        Some(DictLookup {
+            kana: entry.pinyin.clone(),
+            meaning: entry.meaning.clone(),
+            // Traditional text still gets the level of the simplified word.
+            hsk: self
+                .common
+                .get(&entry.simplified)
+                .map(|common| common.level),
+            source: Some(LookupSource::Dictionary),
+            ..Default::default()
+        })
+    }
+}
+
+impl LanguageBackend for ChineseBackend {
+    fn tokenize(&self, text: &str) -> Vec<Token> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut tokens = Vec::new();
+
+        let mut i = 0;
+        while i < chars.len() {
+            // Keep runs of other text together.
+            if !is_han(chars[i]) {
+                let start = i;
+                while i < chars.len() && !is_han(chars[i]) {
+                    i += 1;
+                }
+                tokens.push(Token {
+                    text: chars[start..i].iter().collect(),
+                    is_word: false,
+                    ..Default::default()
+                });
+                continue;
+            }
+
+            let start = i;
+            while i < chars.len() && is_han(chars[i]) {
+                i += 1;
+            }
+            for word in self.segment(&chars[start..i]) {
+                tokens.push(Token {
+                    text: word,
+                    is_word: true,
+                    ..Default::default()
+                });
+            }
+        }
+
+        tokens
+    }
+
+    fn lookup(&self, word: &str) -> Option<DictLookup> {
+        let word = word.trim();
+
+        if let Some(entry) = self.glossary.get(word) {
+            let mut lookup = entry.apply(self.lookup_sources(word));
+            if let Some(level) = entry.level.as_ref().and_then(GlossaryLevel::hsk) {
+                lookup.hsk = Some(level);
+            }
+            return Some(lookup);
+        }
+
+        self.lookup_sources(word)
+    }
+
+    fn contains(&self, word: &str) -> bool {
+        if self.names.iter().any(|name| name == word) || self.glossary.get(word).is_some() {
+            return true;
+        }
+        self.sources.iter().any(|source| match source {
+            Source::Common => self.common.contains_key(word),
+            Source::Dictionary => self.dictionary.contains_key(word),
+            Source::External(dictionary) => dictionary.contains(word),
+        })
+    }
+
+    fn difficulty(&self, _word: &str, lookup: &DictLookup, config: &LanguageConfig) -> Difficulty {
+        let level = lookup.hsk.unwrap_or(HskLevel::None);
+        Difficulty {
+            definition: level >= config.chinese.definitions(),
+            reading: level >= config.chinese.pinyin(),
+        }
+        .with_frequency(level != HskLevel::None, lookup.frequency, &config.frequency)
+    }
+
+    fn tooltip(&self, word: &str, lookup: &DictLookup) -> String {
+        format!(
+            "{}[{}::{}::{}]",
+            word,
+            lookup.kana,
+            lookup.meaning,
+            lookup.hsk.unwrap_or(HskLevel::None)
+        )
+    }
+}
+
+/// Embeded Chinese language data.
+#[derive(RustEmbed)]
+#[folder = "metadata/language/zh"]
+struct ChineseMetadata;
+
+#[derive(Debug, Clone)]
+struct HskVocab {
+    pinyin: String,
+    meaning: String,
+    level: HskLevel,
+}
+
+impl From<&HskVocab> for DictLookup {
+    fn from(value: &HskVocab) -> Self {
+        Self {
+            kana: value.pinyin.clone(),
+            meaning: value.meaning.clone(),
+            hsk: Some(value.level),
+            source: Some(LookupSource::Common),
+            ..Default::default()
+        }
+    }
+}
+
+/// Serde derive class for the CSV format of HSK vocabulary.
+#[derive(Debug, serde::Deserialize)]
+struct HskEntry {
+    word: String,
+    pinyin: String,
+    meaning: String,
+}
+
+/// CC-CEDICT entry, e.g. `中國 中国 [Zhong1 guo2] /China/`.
+#[derive(Debug, Clone)]
+struct CedictEntry {
+    traditional: String,
+    simplified: String,
+    /// Pinyin with tone marks.
+    pinyin: String,
+    meaning: String,
+}
+
+impl CedictEntry {
+    fn parse(line: &str) -> Option<Self> {
+        let (traditional, rest) = line.split_once(' ')?;
+        let (simplified, rest) = rest.split_once(" [")?;
+        let (pinyin, rest) = rest.split_once("] /")?;
+        // Skip measure word notes.
+        let meaning = rest
+            .split('/')
+            .find(|gloss| !gloss.trim().is_empty() && !gloss.starts_with("CL:"))?;
+
+        Some(Self {
+            traditional: traditional.to_string(),
+            simplified: simplified.to_string(),
+            pinyin: tone_marks(pinyin),
+            meaning: meaning.to_string(),
+        })
+    }
+
+    fn is_proper(&self) -> bool {
+        self.pinyin.starts_with(char::is_uppercase)
+    }
+}
+
+/// Check if a character is a Chinese character.
+fn is_han(c: char) -> bool {
+    matches!(c,
+        '\u{3007}'
+        | '\u{3400}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}'
+        | '\u{f900}'..='\u{faff}'
+        | '\u{20000}'..='\u{2ebef}')
+}
+
+/// Convert numbered pinyin, e.g. `nu:3 er2`, to pinyin with tone marks, e.g.
+/// `nǚ'ér`.
+fn tone_marks(pinyin: &str) -> String {
+    let mut marked = String::new();
+    for syllable in pinyin.split_whitespace() {
+        let syllable = syllable.replace("u:", "ü").replace("U:", "Ü");
+        let (body, tone) = match syllable.chars().last().and_then(|c| c.to_digit(10)) {
+            Some(tone) => (&syllable[..syllable.len() - 1], tone),
+            None => (syllable.as_str(), 5),
+        };
+
+        let lower = body.to_lowercase();
+
+        // Syllables starting with a vowel are separated by an apostrophe.
+        if !marked.is_empty() && lower.starts_with(['a', 'e', 'o']) {
+            marked.push('\'');
+        }
+
+        // The tone mark goes on a or e, the o of ou, or otherwise the last
+        // vowel.
+        let position = match (lower.find('a'), lower.find('e'), lower.find("ou")) {
+            (Some(i), _, _) | (None, Some(i), _) | (None, None, Some(i)) => Some(i),
+            _ => lower.rfind(['i', 'o', 'u', 'ü']),
+        };
+        for (i, c) in body.char_indices() {
+            match Some(i) == position {
+                true => marked.push(tone_mark(c, tone)),
+                false => marked.push(c),
+            }
+        }
+    }
+    marked
+}
+
+/// Add a tone mark to a vowel.
+fn tone_mark(vowel: char, tone: u32) -> char {
+    let marks = match vowel {
+        'a' => ['ā', 'á', 'ǎ', 'à'],
+        'e' => ['ē', 'é', 'ě', 'è'],
+        'i' => ['ī', 'í', 'ǐ', 'ì'],
+        'o' => ['ō', 'ó', 'ǒ', 'ò'],
+        'u' => ['ū', 'ú', 'ǔ', 'ù'],
+        'ü' => ['ǖ', 'ǘ', 'ǚ', 'ǜ'],
+        'A' => ['Ā', 'Á', 'Ǎ', 'À'],
+        'E' => ['Ē', 'É', 'Ě', 'È'],
+        'O' => ['Ō', 'Ó', 'Ǒ', 'Ò'],
+        _ => return vowel,
+    };
+    match tone {
+        1..=4 => marks[tone as usize - 1],
+        _ => vowel,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backend() -> ChineseBackend {
+        let mut backend = ChineseBackend::new(&Config::default()).unwrap();
+        backend.load_cedict(
+            "# Test entries\n\
+            研究 研究 [yan2 jiu1] /research/\n\
+            研究生 研究生 [yan2 jiu1 sheng1] /graduate student/\n\
+            生命 生命 [sheng1 ming4] /life/\n\
+            起源 起源 [qi3 yuan2] /origin/\n\
+            結婚 结婚 [jie2 hun1] /to marry/\n\
+            和尚 和尚 [he2 shang5] /Buddhist monk/\n\
+            尚未 尚未 [shang4 wei4] /not yet/\n",
+        );
+        backend
+    }
+
+    fn words(backend: &ChineseBackend, text: &str) -> Vec<String> {
+        backend
+            .tokenize(text)
+            .into_iter()
+            .map(|token| token.text)
+            .collect()
+    }
+
+    #[test]
+    fn segments_ambiguous_text() {
+        let backend = backend();
+        assert_eq!(words(&backend, "研究生命起源"), ["研究", "生命", "起源"]);
+        assert_eq!(
+            words(&backend, "结婚的和尚未结婚的"),
+            ["结婚", "的", "和", "尚未", "结婚", "的"]
+        );
+        assert_eq!(words(&backend, "研究生"), ["研究生"]);
+    }
+
+    #[test]
+    fn keeps_other_text_together() {
+        let tokens = backend().tokenize("abc 结婚!");
+        let words: Vec<(&str, bool)> = tokens
+            .iter()
+            .map(|token| (token.text.as_str(), token.is_word))
+            .collect();
+        assert_eq!(words, [("abc ", false), ("结婚", true), ("!", false)]);
+    }
+
+    #[test]
+    fn marks_tones() {
+        assert_eq!(tone_marks("nu:3 er2"), "nǚ'ér");
+        assert_eq!(tone_marks("lu:4"), "lǜ");
+        assert_eq!(tone_marks("ma1 ma5"), "māma");
+        assert_eq!(tone_marks("xie4 xie"), "xièxie");
+        assert_eq!(tone_marks("Zhong1 guo2"), "Zhōngguó");
+        assert_eq!(tone_marks("xi1 an1"), "xī'ān");
+        assert_eq!(tone_marks("gou3"), "gǒu");
+        assert_eq!(tone_marks("gui4"), "guì");
+    }
+
+    #[test]
+    fn parses_cedict_lines() {
+        let entry = CedictEntry::parse("中國 中国 [Zhong1 guo2] /China/").unwrap();
+        assert_eq!(entry.traditional, "中國");
+        assert_eq!(entry.simplified, "中国");
+        assert_eq!(entry.pinyin, "Zhōngguó");
+        assert_eq!(entry.meaning, "China");
+        assert!(entry.is_proper());
+
+        let entry = CedictEntry::parse("書 书 [shu1] /CL:本[ben3]/book/letter/").unwrap();
+        assert_eq!(entry.meaning, "book");
+        assert!(!entry.is_proper());
+
+        assert!(CedictEntry::parse("書 书 shu1 /book/").is_none());
+        assert!(CedictEntry::parse("").is_none());
+    }
+
+    #[test]
+    fn prefers_common_words_over_proper_nouns() {
+        for content in [
+            "張 张 [Zhang1] /surname Zhang/\n張 张 [zhang1] /sheet/",
+            "張 张 [zhang1] /sheet/\n張 张 [Zhang1] /surname Zhang/",
+        ] {
+            let mut backend = ChineseBackend::new(&Config::default()).unwrap();
+            backend.load_cedict(content);
+            let lookup = backend.lookup_dictionary("張").unwrap();
+            assert_eq!(lookup.meaning, "sheet");
+        }
+    }
+}
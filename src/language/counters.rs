@@ -0,0 +1,667 @@
+//! Japanese numbers and counters, e.g. 三本 read as さんぼん.
+
+use super::*;
+
+/// Read a number followed by a counter, e.g. 3本 or 八日, with the counter's
+/// sound changes.
+pub fn counter_reading(word: &str) -> Option<Reading> {
+    let split = word
+        .char_indices()
+        .find(|(_, c)| digit(*c).is_none() && multiplier(*c).is_none())
+        .map(|(i, _)| i)?;
+    let (number, suffix) = word.split_at(split);
+    let n = parse_number(number)?;
+    let counter = COUNTERS
+        .iter()
+        .find(|counter| counter.counters.contains(&suffix))?;
+
+    let kana = match counter
+        .irregular
+        .iter()
+        .find(|(irregular, _)| *irregular == n)
+    {
+        Some((_, kana)) => kana.to_string(),
+        None if counter.regular => counter.read(n)?,
+        None => return None,
+    };
+    Some(Reading {
+        kana,
+        meaning: format!("{n}, counter for {}", counter.meaning),
+    })
+}
+
+/// Parse Arabic or kanji numerals, e.g. 20, ２０, 二十 or 二〇.
+pub fn parse_number(text: &str) -> Option<u64> {
+    if text.is_empty() {
+        return None;
+    }
+
+    // Positional digits, e.g. 2024 or 二〇二四.
+    if text.chars().all(|c| digit(c).is_some()) {
+        return text
+            .chars()
+            .try_fold(0u64, |n, c| n.checked_mul(10)?.checked_add(digit(c)?));
+    }
+
+    // Kanji numerals with multipliers, e.g. 三千五百.
+    let mut total = 0;
+    let mut section = 0;
+    let mut current: Option<u64> = None;
+    for c in text.chars() {
+        match (digit(c), multiplier(c)) {
+            (Some(_), _) if current.is_some() => return None,
+            (Some(d), _) => current = Some(d),
+            (None, Some(m @ (10 | 100 | 1000))) => section += current.take().unwrap_or(1) * m,
+            (None, Some(m)) => {
+                section += current.take().unwrap_or(0);
+                total += section.max(1) * m;
+                section = 0;
+            }
+            (None, None) => return None,
+        }
+    }
+    Some(total + section + current.unwrap_or(0))
+}
+
+/// Value of an Arabic or kanji digit.
+fn digit(c: char) -> Option<u64> {
+    match c {
+        '0'..='9' => Some(c as u64 - '0' as u64),
+        '０'..='９' => Some(c as u64 - '０' as u64),
+        '〇' | '零' => Some(0),
+        '一' => Some(1),
+        '二' => Some(2),
+        '三' => Some(3),
+        '四' => Some(4),
+        '五' => Some(5),
+        '六' => Some(6),
+        '七' => Some(7),
+        '八' => Some(8),
+        '九' => Some(9),
+        _ => None,
+    }
+}
+
+/// Value of a kanji multiplier.
+fn multiplier(c: char) -> Option<u64> {
+    match c {
+        '十' => Some(10),
+        '百' => Some(100),
+        '千' => Some(1000),
+        '万' => Some(10_000),
+        '億' => Some(100_000_000),
+        _ => None,
+    }
+}
+
+/// Last part of a number's reading, which decides the counter's sound
+/// changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Last {
+    Digit(u64),
+    Ten,
+    Hundred,
+    Thousand,
+    Myriad,
+}
+
+/// Read a number as kana, returning the reading of each part and the last
+/// part.
+fn read_number(n: u64) -> Option<(Vec<String>, Last)> {
+    const KANA: [&str; 10] = [
+        "ぜろ",
+        "いち",
+        "に",
+        "さん",
+        "よん",
+        "ご",
+        "ろく",
+        "なな",
+        "はち",
+        "きゅう",
+    ];
+
+    // Zero is read as the loanword, e.g. ゼロほん for 0本.
+    if n == 0 {
+        return Some((vec!["ゼロ".to_string()], Last::Digit(0)));
+    }
+
+    let mut parts = Vec::new();
+    let mut last = Last::Digit(0);
+    for (unit, unit_kana) in [(100_000_000, "おく"), (10_000, "まん"), (1, "")] {
+        let group = (n / unit) % 10_000;
+        if group == 0 {
+            continue;
+        }
+        if unit == 100_000_000 && n / unit >= 10_000 {
+            return None;
+        }
+        let thousands = group / 1000;
+        let hundreds = group / 100 % 10;
+        let tens = group / 10 % 10;
+        let ones = group % 10;
+        match thousands {
+            0 => {}
+            1 => parts.push("せん".to_string()),
+            3 => parts.push("さんぜん".to_string()),
+            8 => parts.push("はっせん".to_string()),
+            d => parts.push(format!("{}せん", KANA[d as usize])),
+        }
+        match hundreds {
+            0 => {}
+            1 => parts.push("ひゃく".to_string()),
+            3 => parts.push("さんびゃく".to_string()),
+            6 => parts.push("ろっぴゃく".to_string()),
+            8 => parts.push("はっぴゃく".to_string()),
+            d => parts.push(format!("{}ひゃく", KANA[d as usize])),
+        }
+        match tens {
+            0 => {}
+            1 => parts.push("じゅう".to_string()),
+            d => parts.push(format!("{}じゅう", KANA[d as usize])),
+        }
+        if ones > 0 {
+            parts.push(KANA[ones as usize].to_string());
+        }
+        last = match (ones, tens, hundreds) {
+            (0, 0, 0) => Last::Thousand,
+            (0, 0, _) => Last::Hundred,
+            (0, _, _) => Last::Ten,
+            (d, _, _) => Last::Digit(d),
+        };
+        if !unit_kana.is_empty() {
+            parts.push(unit_kana.to_string());
+            last = Last::Myriad;
+        }
+    }
+    Some((parts, last))
+}
+
+/// Sound changes a counter causes, named by the first sound of the counter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Change {
+    /// No sound changes, e.g. 枚.
+    None,
+    /// K sounds double after 1, 6, 8, 10 and 100, e.g. いっこ.
+    K,
+    /// Like `K`, voiced after 3, e.g. さんがい.
+    KVoiced,
+    /// S sounds double after 1, 8 and 10, e.g. いっさつ.
+    S,
+    /// Like `S`, voiced after 3 and 1000, e.g. さんぞく.
+    SVoiced,
+    /// T sounds double after 1, 8 and 10, e.g. いっとう.
+    T,
+    /// H sounds become p after 1, 6, 8, 10 and 100, and b after 3, 1000 and
+    /// 10,000, e.g. いっぽん and さんぼん.
+    H,
+    /// Like `H`, but p after 3, 4, 1000 and 10,000, e.g. さんぷん.
+    HPlosive,
+}
+
+/// A counter, e.g. 本.
+struct Counter {
+    /// Ways to write the counter.
+    counters: &'static [&'static str],
+    /// Reading of the counter.
+    kana: &'static str,
+    /// What is counted.
+    meaning: &'static str,
+    change: Change,
+    /// Readings of 4, 7 and 9 before the counter, e.g. よ for 4時.
+    digits: [&'static str; 3],
+    /// Readings of whole numbers that don't follow the rules, e.g. はたち.
+    irregular: &'static [(u64, &'static str)],
+    /// Whether numbers without an irregular reading can be counted.
+    regular: bool,
+}
+
+impl Counter {
+    /// Read a number followed by the counter.
+    fn read(&self, n: u64) -> Option<String> {
+        let (mut parts, last) = read_number(n)?;
+        let mut counter: Vec<char> = self.kana.chars().collect();
+        let number = parts.last_mut()?;
+
+        if let Last::Digit(d @ (4 | 7 | 9)) = last {
+            let kana = self.digits[match d {
+                4 => 0,
+                7 => 1,
+                _ => 2,
+            }];
+            *number = kana.to_string();
+        }
+
+        let doubles = match (self.change, last) {
+            (Change::None, _) => false,
+            (Change::K | Change::KVoiced | Change::H | Change::HPlosive, last) => {
+                matches!(last, Last::Digit(1 | 6 | 8) | Last::Ten | Last::Hundred)
+            }
+            (Change::S | Change::SVoiced | Change::T, last) => {
+                matches!(last, Last::Digit(1 | 8) | Last::Ten)
+            }
+        };
+        let after_n = number.ends_with('ん');
+        if doubles {
+            // いち, ろく, はち, じゅう and ひゃく lose their last kana.
+            number.pop();
+            number.push('っ');
+        }
+
+        let first = counter.first_mut()?;
+        *first = match (self.change, doubles, after_n, last) {
+            (Change::H | Change::HPlosive, true, _, _) => sound_change(*first, 2),
+            (Change::H, false, true, Last::Digit(3) | Last::Thousand | Last::Myriad) => {
+                sound_change(*first, 1)
+            }
+            (Change::HPlosive, false, true, _) => sound_change(*first, 2),
+            (Change::KVoiced, false, true, Last::Digit(3)) => sound_change(*first, 1),
+            (Change::SVoiced, false, true, Last::Digit(3) | Last::Thousand) => {
+                sound_change(*first, 1)
+            }
+            _ => *first,
+        };
+
+        Some(parts.concat() + &counter.into_iter().collect::<String>())
+    }
+}
+
+/// Voice a kana with an offset of 1, e.g. か to が, or make an h sound a p
+/// sound with an offset of 2, e.g. は to ぱ.
+fn sound_change(kana: char, offset: u32) -> char {
+    let voiceable = "かきくけこさしすせそたちつてとはひふへほ";
+    match voiceable.contains(kana) {
+        true => char::from_u32(kana as u32 + offset).unwrap_or(kana),
+        false => kana,
+    }
+}
+
+/// Readings of 4, 7 and 9 before most counters.
+const DIGITS: [&str; 3] = ["よん", "なな", "きゅう"];
+
+/// Common counters.
+const COUNTERS: &[Counter] = &[
+    Counter {
+        counters: &["本"],
+        kana: "ほん",
+        meaning: "long, thin objects",
+        change: Change::H,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["匹"],
+        kana: "ひき",
+        meaning: "small animals",
+        change: Change::H,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["杯"],
+        kana: "はい",
+        meaning: "cups and bowls",
+        change: Change::H,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["分"],
+        kana: "ふん",
+        meaning: "minutes",
+        change: Change::HPlosive,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["泊"],
+        kana: "はく",
+        meaning: "nights",
+        change: Change::HPlosive,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["個", "コ", "ヶ"],
+        kana: "こ",
+        meaning: "small objects",
+        change: Change::K,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["回"],
+        kana: "かい",
+        meaning: "times",
+        change: Change::K,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["件"],
+        kana: "けん",
+        meaning: "matters and cases",
+        change: Change::K,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["か月", "ヶ月", "ヵ月", "カ月", "ケ月", "箇月", "個月"],
+        kana: "かげつ",
+        meaning: "months",
+        change: Change::K,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["階"],
+        kana: "かい",
+        meaning: "floors",
+        change: Change::KVoiced,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["軒"],
+        kana: "けん",
+        meaning: "buildings",
+        change: Change::KVoiced,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["冊"],
+        kana: "さつ",
+        meaning: "books",
+        change: Change::S,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["歳", "才"],
+        kana: "さい",
+        meaning: "years of age",
+        change: Change::S,
+        digits: DIGITS,
+        irregular: &[(20, "はたち")],
+        regular: true,
+    },
+    Counter {
+        counters: &["週間"],
+        kana: "しゅうかん",
+        meaning: "weeks",
+        change: Change::S,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["足"],
+        kana: "そく",
+        meaning: "pairs of shoes and socks",
+        change: Change::SVoiced,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["頭"],
+        kana: "とう",
+        meaning: "large animals",
+        change: Change::T,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["通"],
+        kana: "つう",
+        meaning: "letters",
+        change: Change::T,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["点"],
+        kana: "てん",
+        meaning: "points",
+        change: Change::T,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["着"],
+        kana: "ちゃく",
+        meaning: "suits and places in a race",
+        change: Change::T,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["枚"],
+        kana: "まい",
+        meaning: "flat objects",
+        change: Change::None,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["台"],
+        kana: "だい",
+        meaning: "machines and vehicles",
+        change: Change::None,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["番"],
+        kana: "ばん",
+        meaning: "numbers in a series",
+        change: Change::None,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["度"],
+        kana: "ど",
+        meaning: "times and degrees",
+        change: Change::None,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["秒"],
+        kana: "びょう",
+        meaning: "seconds",
+        change: Change::None,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["名"],
+        kana: "めい",
+        meaning: "people",
+        change: Change::None,
+        digits: DIGITS,
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["人"],
+        kana: "にん",
+        meaning: "people",
+        change: Change::None,
+        digits: ["よ", "なな", "きゅう"],
+        irregular: &[(1, "ひとり"), (2, "ふたり")],
+        regular: true,
+    },
+    Counter {
+        counters: &["年"],
+        kana: "ねん",
+        meaning: "years",
+        change: Change::None,
+        digits: ["よ", "なな", "きゅう"],
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["円"],
+        kana: "えん",
+        meaning: "yen",
+        change: Change::None,
+        digits: ["よ", "なな", "きゅう"],
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["時"],
+        kana: "じ",
+        meaning: "hours of the day",
+        change: Change::None,
+        digits: ["よ", "しち", "く"],
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["時間"],
+        kana: "じかん",
+        meaning: "hours",
+        change: Change::None,
+        digits: ["よ", "なな", "く"],
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["月"],
+        kana: "がつ",
+        meaning: "months of the year",
+        change: Change::None,
+        digits: ["し", "しち", "く"],
+        irregular: &[],
+        regular: true,
+    },
+    Counter {
+        counters: &["日"],
+        kana: "にち",
+        meaning: "days",
+        change: Change::None,
+        digits: DIGITS,
+        irregular: &[
+            (1, "ついたち"),
+            (2, "ふつか"),
+            (3, "みっか"),
+            (4, "よっか"),
+            (5, "いつか"),
+            (6, "むいか"),
+            (7, "なのか"),
+            (8, "ようか"),
+            (9, "ここのか"),
+            (10, "とおか"),
+            (14, "じゅうよっか"),
+            (20, "はつか"),
+            (24, "にじゅうよっか"),
+        ],
+        regular: true,
+    },
+    Counter {
+        counters: &["つ"],
+        kana: "つ",
+        meaning: "things",
+        change: Change::None,
+        digits: DIGITS,
+        irregular: &[
+            (1, "ひとつ"),
+            (2, "ふたつ"),
+            (3, "みっつ"),
+            (4, "よっつ"),
+            (5, "いつつ"),
+            (6, "むっつ"),
+            (7, "ななつ"),
+            (8, "やっつ"),
+            (9, "ここのつ"),
+            (10, "とお"),
+        ],
+        regular: false,
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kana(word: &str) -> Option<String> {
+        counter_reading(word).map(|reading| reading.kana)
+    }
+
+    #[test]
+    fn parses_numbers() {
+        assert_eq!(parse_number("20"), Some(20));
+        assert_eq!(parse_number("２０"), Some(20));
+        assert_eq!(parse_number("二十"), Some(20));
+        assert_eq!(parse_number("二〇二四"), Some(2024));
+        assert_eq!(parse_number("三千五百"), Some(3500));
+        assert_eq!(parse_number("一万二千"), Some(12_000));
+        assert_eq!(parse_number("十"), Some(10));
+        assert_eq!(parse_number("三三百"), None);
+        assert_eq!(parse_number(""), None);
+    }
+
+    #[test]
+    fn reads_sound_changes() {
+        assert_eq!(kana("3本").as_deref(), Some("さんぼん"));
+        assert_eq!(kana("1本").as_deref(), Some("いっぽん"));
+        assert_eq!(kana("2本").as_deref(), Some("にほん"));
+        assert_eq!(kana("1匹").as_deref(), Some("いっぴき"));
+        assert_eq!(kana("三千本").as_deref(), Some("さんぜんぼん"));
+        assert_eq!(kana("3分").as_deref(), Some("さんぷん"));
+        assert_eq!(kana("4分").as_deref(), Some("よんぷん"));
+        assert_eq!(kana("6個").as_deref(), Some("ろっこ"));
+        assert_eq!(kana("3階").as_deref(), Some("さんがい"));
+        assert_eq!(kana("10冊").as_deref(), Some("じゅっさつ"));
+        assert_eq!(kana("3足").as_deref(), Some("さんぞく"));
+        assert_eq!(kana("4時").as_deref(), Some("よじ"));
+        assert_eq!(kana("9月").as_deref(), Some("くがつ"));
+    }
+
+    #[test]
+    fn reads_irregular_numbers() {
+        assert_eq!(kana("20歳").as_deref(), Some("はたち"));
+        assert_eq!(kana("8日").as_deref(), Some("ようか"));
+        assert_eq!(kana("一日").as_deref(), Some("ついたち"));
+        assert_eq!(kana("2人").as_deref(), Some("ふたり"));
+        assert_eq!(kana("3つ").as_deref(), Some("みっつ"));
+        assert_eq!(kana("11つ"), None);
+    }
+
+    #[test]
+    fn reads_zero() {
+        assert_eq!(kana("0本").as_deref(), Some("ゼロほん"));
+        assert_eq!(kana("0円").as_deref(), Some("ゼロえん"));
+    }
+
+    #[test]
+    fn skips_unknown_counters() {
+        assert_eq!(kana("3羽"), None);
+        assert_eq!(kana("本"), None);
+    }
+}
@@ -0,0 +1,317 @@
+//! Japanese deinflection, to find the dictionary form of conjugated words.
+//!
+//! Conjugations are undone one suffix at a time, like Yomitan, keeping the
+//! chain of conjugations so it can be explained to the reader.
+
+use std::sync::OnceLock;
+
+use super::*;
+
+/// A possible dictionary form of a conjugated word.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct Deinflection {
+    /// Dictionary form.
+    pub base: String,
+    /// Conjugations applied to the dictionary form, in order.
+    pub reasons: Vec<String>,
+}
+
+impl std::fmt::Display for Deinflection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.base)?;
+        for reason in self.reasons.iter() {
+            write!(f, " → {reason}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Find the possible dictionary forms of a word, shortest chains first. The
+/// candidates aren't checked against a dictionary.
+pub fn deinflect(word: &str) -> Vec<Deinflection> {
+    let mut results: Vec<(String, u16, Vec<&'static str>)> =
+        vec![(word.to_string(), ANY, Vec::new())];
+    let mut i = 0;
+    while i < results.len() {
+        let (term, types, reasons) = results[i].clone();
+        i += 1;
+        // Real conjugation chains are short.
+        if reasons.len() >= MAX_CHAIN {
+            continue;
+        }
+        for rule in rules() {
+            if types & rule.types_in == 0 && types != ANY {
+                continue;
+            }
+            let Some(stem) = term.strip_suffix(rule.from.as_str()) else {
+                continue;
+            };
+            if stem.is_empty() && rule.to.is_empty() {
+                continue;
+            }
+            let base = format!("{stem}{}", rule.to);
+            if results.iter().any(|(existing, _, _)| *existing == base) {
+                continue;
+            }
+            let mut chain = vec![rule.reason];
+            chain.extend(reasons.iter());
+            results.push((base, rule.types_out, chain));
+        }
+    }
+
+    results
+        .into_iter()
+        .skip(1)
+        .filter(|(_, types, _)| types & DICTIONARY_FORMS != 0)
+        .map(|(base, _, reasons)| Deinflection {
+            base,
+            reasons: reasons.into_iter().map(String::from).collect(),
+        })
+        .collect()
+}
+
+/// Most conjugations undone for a word.
+const MAX_CHAIN: usize = 6;
+
+// Word types, for which conjugations can follow each other.
+const ANY: u16 = u16::MAX;
+/// Ichidan verbs, e.g. 食べる.
+const V1: u16 = 1 << 0;
+/// Godan verbs, e.g. 書く.
+const V5: u16 = 1 << 1;
+/// する verbs.
+const VS: u16 = 1 << 2;
+/// 来る.
+const VK: u16 = 1 << 3;
+/// い adjectives, including ない and たい forms.
+const ADJ_I: u16 = 1 << 4;
+/// Polite ます forms.
+const MASU: u16 = 1 << 5;
+/// Polite negative ません forms.
+const MASEN: u16 = 1 << 6;
+/// て forms.
+const TE: u16 = 1 << 7;
+/// Nouns that become verbs with する, e.g. 勉強.
+const NOUN_VS: u16 = 1 << 8;
+/// Word types found in a dictionary.
+const DICTIONARY_FORMS: u16 = V1 | V5 | VS | VK | ADJ_I | NOUN_VS;
+
+/// A conjugation, replacing the `from` suffix of a word of `types_in` with
+/// the `to` suffix of a word of `types_out`.
+struct Rule {
+    from: String,
+    to: String,
+    types_in: u16,
+    types_out: u16,
+    reason: &'static str,
+}
+
+/// Godan endings, as (dictionary, i, a, e, o, て, た) forms.
+const GODAN: &[[&str; 7]] = &[
+    ["く", "き", "か", "け", "こ", "いて", "いた"],
+    ["ぐ", "ぎ", "が", "げ", "ご", "いで", "いだ"],
+    ["す", "し", "さ", "せ", "そ", "して", "した"],
+    ["つ", "ち", "た", "て", "と", "って", "った"],
+    ["ぬ", "に", "な", "ね", "の", "んで", "んだ"],
+    ["ぶ", "び", "ば", "べ", "ぼ", "んで", "んだ"],
+    ["む", "み", "ま", "め", "も", "んで", "んだ"],
+    ["る", "り", "ら", "れ", "ろ", "って", "った"],
+    ["う", "い", "わ", "え", "お", "って", "った"],
+];
+
+/// Get the deinflection rules, verbs before adjectives so 買った is 買う.
+fn rules() -> &'static [Rule] {
+    static RULES: OnceLock<Vec<Rule>> = OnceLock::new();
+    RULES.get_or_init(|| {
+        let mut rules = Vec::new();
+        let mut rule = |from: &str, to: &str, types_in: u16, types_out: u16, reason| {
+            rules.push(Rule {
+                from: from.to_string(),
+                to: to.to_string(),
+                types_in,
+                types_out,
+                reason,
+            });
+        };
+
+        // Forms shared by every verb, built on the ます form.
+        rule("ました", "ます", 0, MASU, "past");
+        rule("ません", "ます", MASEN, MASU, "negative");
+        rule("ませんでした", "ません", 0, MASEN, "past");
+        rule("ましょう", "ます", 0, MASU, "volitional");
+        rule("ている", "て", V1, TE, "progressive");
+        rule("でいる", "で", V1, TE, "progressive");
+        rule("てる", "て", V1, TE, "progressive");
+        rule("でる", "で", V1, TE, "progressive");
+        rule("てしまう", "て", V5, TE, "completed");
+        rule("でしまう", "で", V5, TE, "completed");
+
+        // Ichidan verbs.
+        for (from, types_in, reason) in [
+            ("ない", ADJ_I, "negative"),
+            ("た", 0, "past"),
+            ("て", TE, "て form"),
+            ("ます", MASU, "polite"),
+            ("られる", V1, "potential or passive"),
+            ("させる", V1, "causative"),
+            ("よう", 0, "volitional"),
+            ("ろ", 0, "imperative"),
+            ("れば", 0, "conditional"),
+            ("たら", 0, "conditional"),
+            ("たい", ADJ_I, "desire"),
+        ] {
+            rule(from, "る", types_in, V1, reason);
+        }
+
+        // 行く is irregular in its て and た forms.
+        rule("行って", "行く", TE, V5, "て form");
+        rule("行った", "行く", 0, V5, "past");
+
+        // Godan verbs.
+        for [base, i, a, e, o, te, ta] in GODAN.iter().copied() {
+            rule(&format!("{a}ない"), base, ADJ_I, V5, "negative");
+            rule(ta, base, 0, V5, "past");
+            rule(te, base, TE, V5, "て form");
+            rule(&format!("{i}ます"), base, MASU, V5, "polite");
+            rule(&format!("{a}れる"), base, V1, V5, "passive");
+            rule(&format!("{e}る"), base, V1, V5, "potential");
+            rule(&format!("{a}せる"), base, V1, V5, "causative");
+            rule(&format!("{a}される"), base, V1, V5, "causative passive");
+            rule(&format!("{o}う"), base, 0, V5, "volitional");
+            rule(e, base, 0, V5, "imperative");
+            rule(&format!("{e}ば"), base, 0, V5, "conditional");
+            rule(&format!("{ta}ら"), base, 0, V5, "conditional");
+            rule(&format!("{i}たい"), base, ADJ_I, V5, "desire");
+        }
+        // する verbs, and 来る in kana and kanji.
+        for (prefix, base, types) in [("", "する", VS), ("", "くる", VK), ("来", "来る", VK)]
+        {
+            let forms: &[(&str, u16, &'static str)] = match types {
+                VS => &[
+                    ("しない", ADJ_I, "negative"),
+                    ("した", 0, "past"),
+                    ("して", TE, "て form"),
+                    ("します", MASU, "polite"),
+                    ("される", V1, "passive"),
+                    ("させる", V1, "causative"),
+                    ("できる", V1, "potential"),
+                    ("しよう", 0, "volitional"),
+                    ("しろ", 0, "imperative"),
+                    ("すれば", 0, "conditional"),
+                    ("したら", 0, "conditional"),
+                    ("したい", ADJ_I, "desire"),
+                ],
+                _ => &[
+                    ("こない", ADJ_I, "negative"),
+                    ("きた", 0, "past"),
+                    ("きて", TE, "て form"),
+                    ("きます", MASU, "polite"),
+                    ("こられる", V1, "potential or passive"),
+                    ("こさせる", V1, "causative"),
+                    ("こよう", 0, "volitional"),
+                    ("こい", 0, "imperative"),
+                    ("くれば", 0, "conditional"),
+                    ("きたら", 0, "conditional"),
+                    ("きたい", ADJ_I, "desire"),
+                ],
+            };
+            for (form, types_in, reason) in forms.iter().copied() {
+                // 来 replaces the first kana, e.g. 来ない for こない.
+                let form = match prefix {
+                    "" => form.to_string(),
+                    _ => prefix.to_string() + &form.chars().skip(1).collect::<String>(),
+                };
+                rule(&form, base, types_in, types, reason);
+            }
+        }
+        rule("する", "", VS, NOUN_VS, "する verb");
+
+        // い adjectives, including the ない and たい forms of verbs.
+        rule("くない", "い", ADJ_I, ADJ_I, "negative");
+        rule("かった", "い", 0, ADJ_I, "past");
+        rule("くて", "い", 0, ADJ_I, "て form");
+        rule("ければ", "い", 0, ADJ_I, "conditional");
+        rule("かったら", "い", 0, ADJ_I, "conditional");
+        rule("く", "い", 0, ADJ_I, "adverb");
+        rule("さ", "い", 0, ADJ_I, "noun");
+
+        rules
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Get the conjugations leading from a base form to a word, if the base
+    /// form is among its candidates.
+    fn chain(word: &str, base: &str) -> Option<Vec<String>> {
+        deinflect(word)
+            .into_iter()
+            .find(|deinflection| deinflection.base == base)
+            .map(|deinflection| deinflection.reasons)
+    }
+
+    #[test]
+    fn walks_verb_chains() {
+        assert_eq!(
+            chain("食べさせられなかった", "食べる"),
+            Some(vec![
+                "causative".to_string(),
+                "potential or passive".to_string(),
+                "negative".to_string(),
+                "past".to_string(),
+            ])
+        );
+        assert_eq!(
+            chain("書かれました", "書く"),
+            Some(vec![
+                "passive".to_string(),
+                "polite".to_string(),
+                "past".to_string(),
+            ])
+        );
+        assert_eq!(
+            chain("来なかった", "来る"),
+            Some(vec!["negative".to_string(), "past".to_string()])
+        );
+        assert_eq!(
+            chain("勉強しました", "勉強する"),
+            Some(vec!["polite".to_string(), "past".to_string()])
+        );
+    }
+
+    #[test]
+    fn walks_irregular_verbs() {
+        assert_eq!(chain("行った", "行く"), Some(vec!["past".to_string()]));
+    }
+
+    #[test]
+    fn walks_adjective_chains() {
+        assert_eq!(
+            chain("高くなかった", "高い"),
+            Some(vec!["negative".to_string(), "past".to_string()])
+        );
+    }
+
+    #[test]
+    fn lists_short_chains_first() {
+        let lengths: Vec<usize> = deinflect("食べさせられなかった")
+            .iter()
+            .map(|deinflection| deinflection.reasons.len())
+            .collect();
+        assert!(lengths.is_sorted());
+        assert!(lengths.iter().all(|length| *length <= MAX_CHAIN));
+    }
+
+    #[test]
+    fn keeps_words_without_conjugations() {
+        assert!(deinflect("学校").is_empty());
+        assert!(deinflect("静か").is_empty());
+        assert!(deinflect("東京").is_empty());
+        assert_eq!(chain("食べる", "食べる"), None);
+        // Only the negative past is undone, not the adjective's own ending.
+        assert_eq!(chain("高くなかった", "高"), None);
+        assert_eq!(chain("高くなかった", "高く"), None);
+    }
+}
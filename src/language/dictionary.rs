@@ -0,0 +1,515 @@
+//! Dictionary sources.
+
+use std::io::Read;
+
+use super::*;
+
+/// A source of definitions, consulted by a [`LanguageBackend`].
+pub trait Dictionary: Send + Sync {
+    /// Name of the dictionary.
+    fn name(&self) -> &str;
+
+    /// Lookup word.
+    fn lookup(&self, word: &str) -> Option<DictEntry>;
+
+    /// Check if a word is in the dictionary.
+    fn contains(&self, word: &str) -> bool {
+        self.lookup(word).is_some()
+    }
+
+    /// Frequency rank of a word, lower being more frequent.
+    fn frequency(&self, #[allow(unused)] word: &str) -> Option<u64> {
+        None
+    }
+}
+
+/// Dictionary entry.
+#[derive(Debug, Clone)]
+pub struct DictEntry {
+    /// Reading of the word.
+    pub reading: String,
+    /// Meaning of the word.
+    pub meaning: String,
+    /// Tags of the entry, e.g. parts of speech.
+    pub tags: Vec<String>,
+}
+
+/// Where a backend looks up words, in order.
+pub(crate) enum Source {
+    /// The backend's vocabulary lists.
+    Common,
+    /// The backend's default dictionary.
+    Dictionary,
+    /// A dictionary loaded from a file.
+    External(Box<dyn Dictionary>),
+}
+
+/// Load the configured dictionaries for a language. The vocabulary lists are
+/// consulted first and the default dictionary last unless configured
+/// otherwise.
+pub(crate) fn sources(config: &LanguageConfig, language: Language) -> Result<Vec<Source>> {
+    let mut sources = Vec::new();
+    for dictionary in config.dictionaries.iter() {
+        if dictionary.language.unwrap_or(config.language()) != language {
+            continue;
+        }
+        match (&dictionary.path, dictionary.builtin) {
+            (Some(path), None) => sources.push(Source::External(load(path)?)),
+            (None, Some(BuiltinDictionary::Common)) => sources.push(Source::Common),
+            (None, Some(BuiltinDictionary::Dictionary)) => sources.push(Source::Dictionary),
+            _ => bail!("A dictionary needs either a path or a builtin dictionary."),
+        }
+    }
+
+    if !sources
+        .iter()
+        .any(|source| matches!(source, Source::Common))
+    {
+        sources.insert(0, Source::Common);
+    }
+    if !sources
+        .iter()
+        .any(|source| matches!(source, Source::Dictionary))
+    {
+        sources.push(Source::Dictionary);
+    }
+
+    Ok(sources)
+}
+
+/// Load a dictionary file.
+pub fn load(path: &Path) -> Result<Box<dyn Dictionary>> {
+    let data = match std::fs::read(path) {
+        Ok(data) => data,
+        Err(e) => bail!(
+            "Unable to read dictionary '{}': {e}",
+            path.to_string_lossy()
+        ),
+    };
+
+    if path.to_string_lossy().ends_with(".zip") {
+        let dictionary = YomitanDictionary::from_bytes(&data)?;
+        tracing::debug!(
+            "Loaded {} terms from {}.",
+            dictionary.terms.len(),
+            dictionary.title
+        );
+        return Ok(Box::new(dictionary));
+    }
+
+    let extension = path.extension().unwrap_or_default().to_string_lossy();
+    if matches!(extension.as_ref(), "csv" | "tsv" | "txt") {
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        let content = String::from_utf8_lossy(&data);
+        let list = FrequencyList::parse(&name, &content);
+        tracing::debug!("Loaded {} ranks from {}.", list.ranks.len(), list.name);
+        return Ok(Box::new(list));
+    }
+
+    bail!(
+        "Unsupported dictionary format: '{}'",
+        path.to_string_lossy()
+    )
+}
+
+/// Names dictionary in the jmdict-simplified JMnedict json format.
+pub struct JmnedictDictionary {
+    names: HashMap<String, DictEntry>,
+}
+
+impl JmnedictDictionary {
+    /// Read a JMnedict json file from memory.
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let jmnedict: JmnedictFile = match serde_json::from_slice(data) {
+            Ok(jmnedict) => jmnedict,
+            Err(e) => bail!("Unable to parse jmnedict json: {e}"),
+        };
+
+        let mut names = HashMap::new();
+        for word in jmnedict.words {
+            let Some(kana) = word.kana.first() else {
+                continue;
+            };
+            let Some(translation) = word.translation.first() else {
+                continue;
+            };
+            let meaning = match translation.translation.first() {
+                Some(text) => text.text.clone(),
+                None => String::new(),
+            };
+            for text in word.kanji.iter().chain(word.kana.iter()) {
+                // Keep the first entry for a name.
+                names.entry(text.text.clone()).or_insert_with(|| DictEntry {
+                    reading: kana.text.clone(),
+                    meaning: meaning.clone(),
+                    tags: translation.kinds.clone(),
+                });
+            }
+        }
+
+        tracing::debug!("Loaded {} names.", names.len());
+
+        Ok(Self { names })
+    }
+}
+
+impl Dictionary for JmnedictDictionary {
+    fn name(&self) -> &str {
+        "JMnedict"
+    }
+
+    fn lookup(&self, word: &str) -> Option<DictEntry> {
+        self.names.get(word).cloned()
+    }
+
+    fn contains(&self, word: &str) -> bool {
+        self.names.contains_key(word)
+    }
+}
+
+/// Serde derive class for the jmdict-simplified JMnedict json format.
+#[derive(Debug, serde::Deserialize)]
+struct JmnedictFile {
+    words: Vec<JmnedictWord>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct JmnedictWord {
+    #[serde(default)]
+    kanji: Vec<JmnedictText>,
+    #[serde(default)]
+    kana: Vec<JmnedictText>,
+    #[serde(default)]
+    translation: Vec<JmnedictTranslation>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct JmnedictText {
+    text: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct JmnedictTranslation {
+    #[serde(default, rename = "type")]
+    kinds: Vec<String>,
+    #[serde(default)]
+    translation: Vec<JmnedictText>,
+}
+
+/// Dictionary in the Yomitan (formerly Yomichan) zip format.
+pub struct YomitanDictionary {
+    title: String,
+    /// Entries by expression, ordered by score.
+    terms: HashMap<String, Vec<DictEntry>>,
+    /// Entries for words written in their reading.
+    readings: HashMap<String, Vec<DictEntry>>,
+    /// Frequency ranks by expression.
+    frequencies: HashMap<String, u64>,
+}
+
+impl YomitanDictionary {
+    /// Read a Yomitan dictionary zip from memory.
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(data))?;
+
+        let index: YomitanIndex =
+            serde_json::from_str(&read_zip_text(&mut archive, "index.json")?)?;
+        let mut dictionary = Self {
+            title: index.title,
+            terms: HashMap::new(),
+            readings: HashMap::new(),
+            frequencies: HashMap::new(),
+        };
+
+        let names: Vec<String> = archive.file_names().map(String::from).collect();
+
+        // Tags are needed to describe terms, so read them first.
+        let mut tags = HashMap::new();
+        for name in names.iter().filter(|name| name.starts_with("tag_bank_")) {
+            let bank: Vec<YomitanTag> = serde_json::from_str(&read_zip_text(&mut archive, name)?)?;
+            for tag in bank {
+                let notes = match tag.3.is_empty() {
+                    true => tag.0.clone(),
+                    false => tag.3,
+                };
+                tags.insert(tag.0, notes);
+            }
+        }
+
+        let mut scored: Vec<(i64, String, String, DictEntry)> = Vec::new();
+        for name in names.iter().filter(|name| name.starts_with("term_bank_")) {
+            let bank: Vec<Vec<serde_json::Value>> =
+                serde_json::from_str(&read_zip_text(&mut archive, name)?)?;
+            for term in bank {
+                match YomitanDictionary::parse_term(&term, &tags) {
+                    Some(term) => scored.push(term),
+                    None => tracing::warn!("Unable to parse Yomitan term in {name}."),
+                }
+            }
+        }
+        // Keep the highest scoring entries first.
+        scored.sort_by_key(|term| std::cmp::Reverse(term.0));
+        for (_, expression, reading, entry) in scored {
+            if !reading.is_empty() && reading != expression {
+                dictionary
+                    .readings
+                    .entry(reading)
+                    .or_default()
+                    .push(entry.clone());
+            }
+            dictionary.terms.entry(expression).or_default().push(entry);
+        }
+
+        for name in names
+            .iter()
+            .filter(|name| name.starts_with("term_meta_bank_"))
+        {
+            let bank: Vec<Vec<serde_json::Value>> =
+                serde_json::from_str(&read_zip_text(&mut archive, name)?)?;
+            for meta in bank {
+                let (Some(expression), Some("freq"), Some(data)) = (
+                    meta.first().and_then(|v| v.as_str()),
+                    meta.get(1).and_then(|v| v.as_str()),
+                    meta.get(2),
+                ) else {
+                    continue;
+                };
+                let Some(rank) = frequency_value(data) else {
+                    continue;
+                };
+                // Keep the most frequent rank of words with several readings.
+                let reading = data.get("reading").and_then(|v| v.as_str());
+                for word in std::iter::once(expression).chain(reading) {
+                    let frequency = dictionary
+                        .frequencies
+                        .entry(word.to_string())
+                        .or_insert(rank);
+                    *frequency = (*frequency).min(rank);
+                }
+            }
+        }
+
+        Ok(dictionary)
+    }
+
+    /// Parse a term bank entry to its score, expression, reading and entry.
+    fn parse_term(
+        term: &[serde_json::Value],
+        tags: &HashMap<String, String>,
+    ) -> Option<(i64, String, String, DictEntry)> {
+        let expression = term.first()?.as_str()?.to_string();
+        let reading = term.get(1)?.as_str().unwrap_or_default().to_string();
+        let score = term.get(4).and_then(|v| v.as_i64()).unwrap_or_default();
+
+        // Version 1 dictionaries list definitions as the remaining values,
+        // while later versions add a sequence number and term tags.
+        let (glossary, tag_indexes): (Vec<&serde_json::Value>, &[usize]) = match term.get(5)? {
+            serde_json::Value::Array(glossary) => (glossary.iter().collect(), &[2, 7]),
+            _ => (term[5..].iter().collect(), &[2]),
+        };
+        let meaning = glossary
+            .into_iter()
+            .map(glossary_text)
+            .find(|text| !text.is_empty())?;
+
+        let mut entry_tags = Vec::new();
+        for index in tag_indexes.iter().copied() {
+            let Some(names) = term.get(index).and_then(|v| v.as_str()) else {
+                continue;
+            };
+            for name in names.split_whitespace() {
+                let tag = match tags.get(name) {
+                    Some(notes) => notes.clone(),
+                    None => name.to_string(),
+                };
+                if !entry_tags.contains(&tag) {
+                    entry_tags.push(tag);
+                }
+            }
+        }
+
+        let entry = DictEntry {
+            reading: match reading.is_empty() {
+                true => expression.clone(),
+                false => reading.clone(),
+            },
+            meaning,
+            tags: entry_tags,
+        };
+        Some((score, expression, reading, entry))
+    }
+}
+
+impl Dictionary for YomitanDictionary {
+    fn name(&self) -> &str {
+        &self.title
+    }
+
+    fn lookup(&self, word: &str) -> Option<DictEntry> {
+        match self.terms.get(word) {
+            Some(entries) => entries.first().cloned(),
+            None => self.readings.get(word)?.first().cloned(),
+        }
+    }
+
+    fn contains(&self, word: &str) -> bool {
+        self.terms.contains_key(word) || self.readings.contains_key(word)
+    }
+
+    fn frequency(&self, word: &str) -> Option<u64> {
+        self.frequencies.get(word).copied()
+    }
+}
+
+/// Serde derive class for a Yomitan index.
+#[derive(Debug, serde::Deserialize)]
+struct YomitanIndex {
+    title: String,
+}
+
+/// Serde derive class for a Yomitan tag, as name, category, order, notes and
+/// score.
+#[derive(Debug, serde::Deserialize)]
+#[allow(unused)]
+struct YomitanTag(String, String, i64, String, i64);
+
+/// Read a text file from a zip.
+fn read_zip_text(
+    archive: &mut zip::ZipArchive<std::io::Cursor<&[u8]>>,
+    name: &str,
+) -> Result<String> {
+    let mut file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(e) => bail!("Unable to find {name} in dictionary: {e}"),
+    };
+    let mut text = String::new();
+    file.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// Get the plain text of a Yomitan definition, which is either a string or
+/// structured content. Block elements, like list items, are separated by
+/// semicolons.
+fn glossary_text(value: &serde_json::Value) -> String {
+    let mut text = String::new();
+    collect_text(value, &mut text);
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<&str>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<String>>()
+        .join("; ")
+}
+
+fn collect_text(value: &serde_json::Value, text: &mut String) {
+    match value {
+        serde_json::Value::String(s) => *text += s,
+        serde_json::Value::Array(values) => {
+            for value in values {
+                collect_text(value, text);
+            }
+        }
+        serde_json::Value::Object(object) => {
+            // Images have no text and text definitions keep it in a field.
+            if let Some(serde_json::Value::String(s)) = object.get("text") {
+                *text += s;
+            }
+            if let Some(content) = object.get("content") {
+                collect_text(content, text);
+            }
+            if let Some(serde_json::Value::String(tag)) = object.get("tag") {
+                if matches!(tag.as_str(), "li" | "div" | "p" | "br") {
+                    *text += "\n";
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Get the rank of a Yomitan frequency, which is either a number, a string or
+/// an object with the value.
+fn frequency_value(value: &serde_json::Value) -> Option<u64> {
+    match value {
+        serde_json::Value::Number(n) => n.as_u64(),
+        serde_json::Value::String(s) => s.split_whitespace().next()?.parse().ok(),
+        serde_json::Value::Object(object) => match object.get("frequency") {
+            Some(frequency) => frequency_value(frequency),
+            None => frequency_value(object.get("value")?),
+        },
+        _ => None,
+    }
+}
+
+/// Frequency list from a corpus, e.g. novels or subtitles. It only provides
+/// frequency ranks, so it never defines a word.
+pub struct FrequencyList {
+    name: String,
+    ranks: HashMap<String, u64>,
+}
+
+impl FrequencyList {
+    /// Parse a frequency list with one word per line, most frequent first.
+    /// Lines may also have a rank or an occurrence count, separated by a
+    /// comma or a tab. Counts are replaced with the position in the list.
+    pub fn parse(name: &str, content: &str) -> Self {
+        let mut words = Vec::new();
+        for line in content.lines() {
+            let line = line.trim_start_matches('\u{feff}').trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut word = None;
+            let mut number = None;
+            for column in line.split(['\t', ',']).map(str::trim) {
+                match column.parse::<u64>() {
+                    Ok(value) if number.is_none() => number = Some(value),
+                    _ if word.is_none() => word = Some(column),
+                    _ => {}
+                }
+            }
+            if let Some(word) = word {
+                words.push((word.to_string(), number));
+            }
+        }
+        // Skip the header of lists with ranks or counts.
+        if words.first().is_some_and(|(_, number)| number.is_none())
+            && words.iter().any(|(_, number)| number.is_some())
+        {
+            words.remove(0);
+        }
+
+        // Counts decrease down the list, while ranks increase.
+        let counts = match (words.first(), words.last()) {
+            (Some((_, Some(first))), Some((_, Some(last)))) => first > last,
+            _ => true,
+        };
+        let mut ranks = HashMap::new();
+        for (i, (word, number)) in words.into_iter().enumerate() {
+            let rank = match (counts, number) {
+                (false, Some(rank)) => rank,
+                _ => i as u64 + 1,
+            };
+            // Keep the most frequent rank of words listed more than once.
+            ranks.entry(word).or_insert(rank);
+        }
+
+        Self {
+            name: name.to_string(),
+            ranks,
+        }
+    }
+}
+
+impl Dictionary for FrequencyList {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn lookup(&self, _word: &str) -> Option<DictEntry> {
+        None
+    }
+
+    fn frequency(&self, word: &str) -> Option<u64> {
+        self.ranks.get(word).copied()
+    }
+}
@@ -0,0 +1,95 @@
+//! Glossaries of series terms, e.g. invented words and fixed translations.
+
+use super::*;
+
+/// Glossary built from the configured entries and glossary csv files.
+///
+/// Later entries override earlier ones, so a book config can override the
+/// terms of a series config. Entries in the config override those in files.
+#[derive(Debug, Clone, Default)]
+pub struct Glossary {
+    entries: HashMap<String, GlossaryEntry>,
+}
+
+impl Glossary {
+    /// Create a glossary from the language config.
+    pub fn new(config: &LanguageConfig) -> Result<Self> {
+        let mut glossary = Self::default();
+        for path in config.glossary_files.iter() {
+            let content = match std::fs::read_to_string(path) {
+                Ok(content) => content,
+                Err(e) => bail!("Unable to read glossary '{}': {e}", path.to_string_lossy()),
+            };
+            glossary.load_csv(&content)?;
+        }
+        for entry in config.glossary.iter() {
+            glossary.insert(entry.clone());
+        }
+
+        Ok(glossary)
+    }
+
+    /// Load a glossary csv, with a `word,reading,meaning,level` header. Only
+    /// the word column is required.
+    pub fn load_csv(&mut self, content: &str) -> Result<()> {
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(content.as_bytes());
+        for result in reader.deserialize() {
+            let entry: GlossaryEntry = match result {
+                Ok(entry) => entry,
+                Err(e) => bail!("Unable to parse glossary entry: {e}"),
+            };
+            self.insert(entry);
+        }
+
+        Ok(())
+    }
+
+    /// Add an entry. Fields set in the entry replace those of any existing
+    /// entry for the word.
+    pub fn insert(&mut self, mut entry: GlossaryEntry) {
+        if entry.word.is_empty() {
+            return;
+        }
+        if let Some(existing) = self.entries.remove(&entry.word) {
+            if entry.reading.is_empty() {
+                entry.reading = existing.reading;
+            }
+            if entry.meaning.is_empty() {
+                entry.meaning = existing.meaning;
+            }
+            if entry.level.is_none() {
+                entry.level = existing.level;
+            }
+        }
+        self.entries.insert(entry.word.clone(), entry);
+    }
+
+    /// Get the entry for a word.
+    pub fn get(&self, word: &str) -> Option<&GlossaryEntry> {
+        self.entries.get(word)
+    }
+
+    /// Iterate over the words in the glossary.
+    pub fn words(&self) -> impl Iterator<Item = &String> {
+        self.entries.keys()
+    }
+}
+
+impl GlossaryEntry {
+    /// Apply the entry over a dictionary lookup of the same word. Fields left
+    /// empty in the entry keep the dictionary's values.
+    pub fn apply(&self, lookup: Option<DictLookup>) -> DictLookup {
+        let mut lookup = lookup.unwrap_or_default();
+        lookup.source = Some(LookupSource::Glossary);
+        if !self.reading.is_empty() {
+            lookup.kana = self.reading.clone();
+        }
+        if !self.meaning.is_empty() {
+            lookup.meaning = self.meaning.clone();
+        }
+        lookup
+    }
+}
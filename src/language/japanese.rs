@@ -0,0 +1,863 @@
+//! Japanese language backend.
+
+use super::*;
+
+/// Japanese backend, using the JLPT and kore 6k vocabulary lists and jmdict.
+pub struct JapaneseBackend {
+    common: HashMap<String, CommonVocab>,
+    /// Entries loaded from a dictionary file, as (reading, meaning).
+    dictionary: HashMap<String, (String, String)>,
+    /// Every reading of words written in kanji, from the vocabulary lists and
+    /// dictionary file.
+    readings: HashMap<String, Vec<Reading>>,
+    /// Where words are looked up, in order.
+    sources: Vec<Source>,
+    /// Names dictionary, e.g. JMnedict.
+    names: Option<Box<dyn Dictionary>>,
+    /// Series terms, consulted before any other source.
+    glossary: Glossary,
+    /// Kanji dictionary, e.g. KANJIDIC2.
+    kanjidic: Option<Kanjidic>,
+    /// Pitch accents, e.g. from Kanjium.
+    accents: Option<PitchAccents>,
+    tokenizer: Box<dyn Tokenizer>,
+    #[allow(unused)]
+    config: LanguageConfig,
+    #[cfg(feature = "jp")]
+    dict: jmdict_fast::Dict<'static>,
+}
+
+impl JapaneseBackend {
+    /// Create a new JapaneseBackend.
+    pub fn new(config: &Config) -> Result<Self> {
+        #[cfg(feature = "jp")]
+        let dict = jmdict_fast::Dict::load_default()?;
+
+        if config.language.japanese.kanji_grade.is_some()
+            && config.language.japanese.kanjidic.is_none()
+        {
+            bail!("The kanji grade needs a KANJIDIC2 file to be set.");
+        }
+
+        let mut backend = Self {
+            common: HashMap::new(),
+            dictionary: HashMap::new(),
+            readings: HashMap::new(),
+            sources: dictionary::sources(&config.language, Language::Japanese)?,
+            names: match &config.language.japanese.names {
+                Some(path) => Some(load_names(path)?),
+                None => None,
+            },
+            glossary: Glossary::new(&config.language)?,
+            kanjidic: match &config.language.japanese.kanjidic {
+                Some(path) => Some(Kanjidic::load(path)?),
+                None => None,
+            },
+            accents: match &config.language.japanese.accents {
+                Some(path) => Some(PitchAccents::load(path)?),
+                None => None,
+            },
+            tokenizer: japanese_tokenizer(
+                config.language.japanese.tokenizer(),
+                config.language.japanese.lindera_mode(),
+            )?,
+            #[cfg(feature = "jp")]
+            dict,
+            config: config.language.clone(),
+        };
+
+        backend.read_kore_csv("kore_6k/kore.csv")?;
+
+        backend.read_jlpt_csv("jlpt/n1.csv", 1)?;
+        backend.read_jlpt_csv("jlpt/n2.csv", 2)?;
+        backend.read_jlpt_csv("jlpt/n3.csv", 3)?;
+        backend.read_jlpt_csv("jlpt/n4.csv", 4)?;
+        backend.read_jlpt_csv("jlpt/n5.csv", 5)?;
+
+        tracing::debug!("Parsed {} JLPT entries.", backend.common.len());
+
+        Ok(backend)
+    }
+
+    /// Read a jlpt csv.
+    fn read_jlpt_csv(&mut self, file: &str, level: u8) -> Result<()> {
+        let content = read_embedded_text::<JapaneseMetadata>(file)?;
+        // let file = std::fs::File::open(file)?;
+        // let mut reader = csv::ReaderBuilder::new().from_reader(std::io::BufReader::new(file));
+        let mut reader =
+            csv::ReaderBuilder::new().from_reader(std::io::BufReader::new(content.as_bytes()));
+        // let mut reader = csv::ReaderBuilder::new().from_reader(&content);
+        for result in reader.deserialize() {
+            // Notice that we need to provide a type hint for automatic
+            // deserialization.
+            let mut record: JlptEntry = match result {
+                Ok(record) => record,
+                Err(e) => {
+                    tracing::warn!("Unable to parse JlptEntry: {e}");
+                    continue;
+                }
+            };
+            record.level = level;
+            self.add_common(record.into());
+        }
+
+        Ok(())
+    }
+
+    /// Read the kore 6k csv.
+    fn read_kore_csv(&mut self, file: &str) -> Result<()> {
+        let content = read_embedded_text::<JapaneseMetadata>(file)?;
+        let mut reader =
+            csv::ReaderBuilder::new().from_reader(std::io::BufReader::new(content.as_bytes()));
+        for result in reader.deserialize() {
+            let record: KoreEntry = match result {
+                Ok(record) => record,
+                Err(e) => {
+                    tracing::warn!("Unable to parse KoreEntry: {e}");
+                    continue;
+                }
+            };
+            self.add_common(record.into());
+        }
+
+        Ok(())
+    }
+
+    /// Load a dictionary in the jmdict-simplified json format. This allows
+    /// lookups without the `jp` feature, e.g., from a blob fetched by the
+    /// browser.
+    pub fn load_jmdict_json(&mut self, data: &[u8]) -> Result<()> {
+        let jmdict: JmdictFile = match serde_json::from_slice(data) {
+            Ok(jmdict) => jmdict,
+            Err(e) => bail!("Unable to parse jmdict json: {e}"),
+        };
+
+        for word in jmdict.words {
+            for kanji in word.kanji.iter() {
+                // Readings with priority tags come first.
+                let mut readings: Vec<Reading> = word
+                    .kana
+                    .iter()
+                    .filter(|kana| applies_to(&kana.applies_to_kanji, &kanji.text))
+                    .filter_map(|kana| {
+                        Some(Reading {
+                            kana: kana.text.clone(),
+                            meaning: word.gloss(&kana.text)?,
+                        })
+                    })
+                    .collect();
+                readings.sort_by_key(|reading| !word.is_common(&reading.kana));
+                self.add_readings(&kanji.text, &readings);
+                if let Some(reading) = readings.first() {
+                    // Keep the first, usually most common, entry for a word.
+                    self.dictionary
+                        .entry(kanji.text.clone())
+                        .or_insert_with(|| (reading.kana.clone(), reading.meaning.clone()));
+                }
+            }
+            for kana in word.kana.iter() {
+                let Some(gloss) = word.gloss(&kana.text) else {
+                    continue;
+                };
+                self.dictionary
+                    .entry(kana.text.clone())
+                    .or_insert_with(|| (kana.text.clone(), gloss));
+            }
+        }
+
+        tracing::debug!("Loaded {} dictionary entries.", self.dictionary.len());
+
+        Ok(())
+    }
+
+    /// Add common word.
+    fn add_common(&mut self, common: CommonVocab) -> bool {
+        // Don't add single-character hiragana.
+        if common.word.is_kana() && common.word.character_count() == 1 {
+            return false;
+        }
+
+        // Otherwise insert both the word and its reading.
+        if common.word != common.reading {
+            self.add_readings(
+                &common.word,
+                &[Reading {
+                    kana: common.reading.clone(),
+                    meaning: common.meaning.clone(),
+                }],
+            );
+            self.common.insert(common.word.clone(), common.clone());
+        }
+        self.common.insert(common.reading.clone(), common);
+        true
+    }
+
+    /// Add readings of a word, skipping ones already known.
+    fn add_readings(&mut self, word: &str, readings: &[Reading]) {
+        let known = self.readings.entry(word.to_string()).or_default();
+        for reading in readings.iter() {
+            if !known.iter().any(|known| known.kana == reading.kana) {
+                known.push(reading.clone());
+            }
+        }
+    }
+
+    /// Check if a word has kanji above a school grade. Kanji missing from the
+    /// kanji dictionary are above every grade.
+    fn has_kanji_above(&self, word: &str, grade: u8) -> bool {
+        word.chars()
+            .filter(|c| wana_kana::utils::is_char_kanji(*c))
+            .any(
+                |c| match self.kanjidic.as_ref().and_then(|kanjidic| kanjidic.get(c)) {
+                    Some(kanji) => kanji.above_grade(grade),
+                    None => true,
+                },
+            )
+    }
+
+    /// Lookup word in the configured sources.
+    fn lookup_sources(&self, word: &str) -> Option<DictLookup> {
+        // Skip english words, numerals, etc.
+        if word.is_english() {
+            return None;
+        }
+
+        let mut lookup = self.sources.iter().find_map(|source| match source {
+            Source::Common => self.common.get(word).map(DictLookup::from),
+            Source::Dictionary => self.lookup_dictionary(word),
+            Source::External(dictionary) => {
+                let entry = dictionary.lookup(word)?;
+                Some(DictLookup {
+                    is_kana: word.is_kana(),
+                    kana: entry.reading,
+                    meaning: entry.meaning,
+                    tags: entry.tags,
+                    source: Some(LookupSource::External),
+                    ..Default::default()
+                })
+            }
+        })?;
+
+        // Other sources may still know the level or frequency of the word.
+        if lookup.jlpt == JlptLevel::None {
+            if let Some(common) = self.common.get(word) {
+                lookup.jlpt = common.level.into();
+            }
+        }
+        if lookup.frequency.is_none() {
+            lookup.frequency = self.sources.iter().find_map(|source| match source {
+                Source::External(dictionary) => dictionary.frequency(word),
+                _ => None,
+            });
+        }
+        if lookup.readings.is_empty() {
+            if let Some(readings) = self
+                .readings
+                .get(word)
+                .filter(|readings| readings.len() > 1)
+            {
+                lookup.readings = readings.clone();
+                // The reading found is the most common one.
+                lookup
+                    .readings
+                    .sort_by_key(|reading| reading.kana != lookup.kana);
+            }
+        }
+
+        Some(lookup)
+    }
+
+    /// Lookup the dictionary form of a conjugated word, keeping the chain of
+    /// conjugations.
+    fn lookup_deinflected(&self, word: &str) -> Option<DictLookup> {
+        if !self.config.approximate() || word.is_english() || self.contains(word) {
+            return None;
+        }
+
+        deinflect(word).into_iter().find_map(|deinflection| {
+            if !self.contains(&deinflection.base) {
+                return None;
+            }
+            let mut lookup = self.lookup(&deinflection.base)?;
+            lookup.is_kana = word.is_kana();
+            lookup.deinflection = Some(deinflection);
+            Some(lookup)
+        })
+    }
+
+    /// Lookup a number followed by a counter, e.g. 3本, unless the
+    /// vocabulary lists or dictionary file have the word.
+    fn lookup_counter(&self, word: &str) -> Option<DictLookup> {
+        if self.common.contains_key(word) || self.dictionary.contains_key(word) {
+            return None;
+        }
+        let reading = counter_reading(word)?;
+        Some(DictLookup {
+            kana: reading.kana,
+            meaning: reading.meaning,
+            source: Some(LookupSource::Counter),
+            ..Default::default()
+        })
+    }
+
+    /// Lookup word in the loaded dictionary or jmdict.
+    fn lookup_dictionary(&self, word: &str) -> Option<DictLookup> {
+        // Check any loaded dictionary.
+        if let Some((kana, meaning)) = self.dictionary.get(word) {
+            return Some(DictLookup {
+                is_kana: word.is_kana(),
+                kana: kana.clone(),
+                meaning: meaning.clone(),
+                source: Some(LookupSource::Dictionary),
+                ..Default::default()
+            });
+        }
+
+        #[cfg(feature = "jp")]
+        {
+            // Otherwise we look the word up.
+            let mut source = LookupSource::Jmdict;
+            let results = match self.config.approximate() {
+                false => self.dict.lookup_exact(word),
+                true => {
+                    let deinflected = self.dict.lookup_exact_with_deinflection(word);
+                    if deinflected.len() > 0 {
+                        // If a deinflected word is common, we'll use that.
+                        for entry in deinflected.iter() {
+                            for kanji in entry.kanji.iter() {
+                                if let Some(common) = self.common.get(&kanji.text) {
+                                    return Some(common.into());
+                                }
+                            }
+                            for kana in entry.kana.iter() {
+                                if let Some(common) = self.common.get(&kana.text) {
+                                    return Some(common.into());
+                                }
+                            }
+                        }
+
+                        // Otherwise, we use teh deinflected word.
+                        if !deinflected.iter().any(|entry| {
+                            entry.kanji.iter().any(|kanji| kanji.text == word)
+                                || entry.kana.iter().any(|kana| kana.text == word)
+                        }) {
+                            source = LookupSource::JmdictDeinflected;
+                        }
+                        deinflected
+                    } else if let Some(name) = self.lookup_name(word) {
+                        // Names would otherwise get a partial match.
+                        return Some(name);
+                    } else {
+                        source = LookupSource::JmdictPartial;
+                        self.dict.lookup_partial(word)
+                    }
+                }
+            };
+            if results.len() == 0 {
+                return None;
+            }
+
+            if results[0].kana.len() == 0
+                || results[0].sense.len() == 0
+                || results[0].sense[0].gloss.len() == 0
+            {
+                return None;
+            }
+
+            // Every reading of the entries written this way.
+            let mut readings: Vec<Reading> = Vec::new();
+            for entry in results.iter().filter(|entry| {
+                entry.kanji.iter().any(|kanji| kanji.text == word)
+                    && !entry.sense.is_empty()
+                    && !entry.sense[0].gloss.is_empty()
+            }) {
+                for kana in entry.kana.iter() {
+                    if !readings.iter().any(|reading| reading.kana == kana.text) {
+                        readings.push(Reading {
+                            kana: kana.text.clone(),
+                            meaning: entry.sense[0].gloss[0].text.clone(),
+                        });
+                    }
+                }
+            }
+            if readings.len() < 2 {
+                readings.clear();
+            }
+
+            return Some(DictLookup {
+                is_kana: word.trim().is_kana(),
+                kana: results[0].kana[0].text.clone(),
+                meaning: results[0].sense[0].gloss[0].text.clone(),
+                readings,
+                source: Some(source),
+                ..Default::default()
+            });
+        }
+
+        #[cfg(not(feature = "jp"))]
+        None
+    }
+}
+
+impl LanguageBackend for JapaneseBackend {
+    fn tokenize(&self, text: &str) -> Vec<Token> {
+        self.tokenizer.tokenize(text)
+    }
+
+    fn lookup(&self, word: &str) -> Option<DictLookup> {
+        let word = word.trim();
+
+        let mut lookup = match self.glossary.get(word) {
+            Some(entry) => {
+                let mut lookup = entry.apply(self.lookup_sources(word));
+                lookup.is_kana = word.is_kana();
+                if let Some(level) = entry.level.as_ref().and_then(GlossaryLevel::jlpt) {
+                    lookup.jlpt = level;
+                }
+                lookup
+            }
+            None => match self.lookup_deinflected(word) {
+                // The dictionary form already has its kanji and pitch.
+                Some(lookup) => return Some(lookup),
+                None => match self.lookup_counter(word) {
+                    Some(lookup) => lookup,
+                    None => self.lookup_sources(word)?,
+                },
+            },
+        };
+        if let Some(kanjidic) = &self.kanjidic {
+            lookup.kanji = kanjidic.word(word);
+        }
+        if let Some(accents) = &self.accents {
+            if let Some(pitch) = accents.get(word, &lookup.kana) {
+                lookup.pitch = pitch.to_vec();
+            }
+        }
+
+        Some(lookup)
+    }
+
+    fn lookup_name(&self, word: &str) -> Option<DictLookup> {
+        let entry = self.names.as_ref()?.lookup(word.trim())?;
+        Some(DictLookup::name(
+            &entry.reading,
+            name_kind(&entry.tags),
+            &entry.meaning,
+        ))
+    }
+
+    fn contains(&self, word: &str) -> bool {
+        if self.glossary.get(word).is_some()
+            || self.config.names.iter().any(|name| name.name == word)
+            || counter_reading(word).is_some()
+            || self.common.contains_key(word)
+            || self.dictionary.contains_key(word)
+            || self
+                .names
+                .as_ref()
+                .is_some_and(|names| names.contains(word))
+        {
+            return true;
+        }
+        if self.sources.iter().any(|source| match source {
+            Source::External(dictionary) => dictionary.contains(word),
+            _ => false,
+        }) {
+            return true;
+        }
+
+        #[cfg(feature = "jp")]
+        {
+            if !self.dict.lookup_exact(word).is_empty() {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn lookup_characters(&self, word: &str) -> Option<DictLookup> {
+        let kanji = self.kanjidic.as_ref()?.word(word.trim());
+        if kanji.is_empty() {
+            return None;
+        }
+        Some(DictLookup {
+            kanji,
+            source: Some(LookupSource::Characters),
+            ..Default::default()
+        })
+    }
+
+    fn is_name_suffix(&self, token: &Token) -> bool {
+        NAME_SUFFIXES.contains(&token.text.as_str())
+    }
+
+    fn choose_reading(
+        &self,
+        token: &Token,
+        previous: Option<&Token>,
+        next: Option<&Token>,
+        lookup: &mut DictLookup,
+    ) {
+        use wana_kana::ConvertJapanese;
+
+        let word = token.text.as_str();
+        let context = CONTEXT_READINGS
+            .iter()
+            .find(|(context_word, _, before, after)| {
+                *context_word == word
+                    && (before.is_empty()
+                        || previous.is_some_and(|previous| previous.text.ends_with(before)))
+                    && (after.is_empty() || next.is_some_and(|next| next.text.starts_with(after)))
+            });
+        // The tokenizer's reading is only of the text as written when it
+        // wasn't given a base form.
+        let tokenizer = token
+            .reading
+            .as_ref()
+            .filter(|_| token.base.as_deref().is_none_or(|base| base == word));
+        let reason = match (context, tokenizer) {
+            (Some((_, reading, _, _)), _) => {
+                lookup.select_reading(reading);
+                "context"
+            }
+            (None, Some(reading)) => {
+                let reading = reading.to_hiragana();
+                // Trust the tokenizer over the dictionary's readings.
+                if !lookup.select_reading(&reading) {
+                    lookup.kana = reading;
+                }
+                "tokenizer"
+            }
+            (None, None) => "most common",
+        };
+
+        // Report ambiguous words so the choices can be audited.
+        tracing::debug!(
+            "Ambiguous reading: {}【{word}】{} read as {} by {reason}, out of {}.",
+            previous
+                .map(|token| token.text.as_str())
+                .unwrap_or_default(),
+            next.map(|token| token.text.as_str()).unwrap_or_default(),
+            lookup.kana,
+            lookup
+                .readings
+                .iter()
+                .map(|reading| reading.kana.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+    }
+
+    fn difficulty(&self, word: &str, lookup: &DictLookup, config: &LanguageConfig) -> Difficulty {
+        // If this is a single character kana, skip.
+        // TODO: This should be smarter.
+        if lookup.is_kana && (word.character_count() == 1 || lookup.kana.character_count() == 1) {
+            return Difficulty::default();
+        }
+
+        if let Some(grade) = config.japanese.kanji_grade {
+            if !self.has_kanji_above(word, grade) {
+                return Difficulty::default();
+            }
+        }
+
+        Difficulty {
+            definition: lookup.jlpt <= config.japanese.definitions(),
+            reading: lookup.jlpt <= config.japanese.furigana(),
+        }
+        .with_frequency(
+            lookup.jlpt != JlptLevel::None,
+            lookup.frequency,
+            &config.frequency,
+        )
+    }
+
+    fn tooltip(&self, word: &str, lookup: &DictLookup) -> String {
+        let mut kana = lookup.kana.clone();
+        if !lookup.pitch.is_empty() {
+            kana.push(' ');
+            kana.extend(lookup.pitch.iter().map(|downstep| pitch_mark(*downstep)));
+        }
+
+        // With a kanji grade, only the kanji above it are of interest.
+        let mut meaning = lookup.meaning.clone();
+        for kanji in lookup.kanji.iter() {
+            if let Some(grade) = self.config.japanese.kanji_grade {
+                if !kanji.above_grade(grade) {
+                    continue;
+                }
+            }
+            if !meaning.is_empty() {
+                meaning += " | ";
+            }
+            meaning += &kanji.to_string();
+        }
+        if self.config.japanese.deinflection() {
+            if let Some(deinflection) = &lookup.deinflection {
+                if !meaning.is_empty() {
+                    meaning += " | ";
+                }
+                meaning += &deinflection.to_string();
+            }
+        }
+
+        format!("{}[{}::{}::{}]", word, kana, meaning, lookup.jlpt)
+    }
+}
+
+/// Readings that depend on the neighbouring tokens, as (word, reading,
+/// previous token ending, next token beginning). Empty conditions match any
+/// token.
+const CONTEXT_READINGS: &[(&str, &str, &str, &str)] = &[
+    // Days of the month, e.g. 四月一日.
+    ("一日", "ついたち", "月", ""),
+    ("二十日", "はつか", "月", ""),
+    ("一日", "いちにち", "", "中"),
+    // 何 is read なん before d, n and t sounds.
+    ("何", "なん", "", "で"),
+    ("何", "なん", "", "だ"),
+    ("何", "なん", "", "の"),
+    ("何", "なん", "", "と"),
+    ("何", "なに", "", "を"),
+    ("何", "なに", "", "が"),
+    ("何", "なに", "", "も"),
+    // 上手 is an upper hand before を, e.g. 上手を取る.
+    ("上手", "うわて", "", "を"),
+];
+
+/// Honorifics and titles that follow names.
+const NAME_SUFFIXES: &[&str] = &[
+    "さん",
+    "さま",
+    "様",
+    "くん",
+    "君",
+    "ちゃん",
+    "殿",
+    "氏",
+    "先生",
+    "先輩",
+];
+
+/// Load a names dictionary.
+fn load_names(path: &Path) -> Result<Box<dyn Dictionary>> {
+    if !path.to_string_lossy().ends_with(".json") {
+        return dictionary::load(path);
+    }
+
+    let data = match std::fs::read(path) {
+        Ok(data) => data,
+        Err(e) => bail!(
+            "Unable to read names dictionary '{}': {e}",
+            path.to_string_lossy()
+        ),
+    };
+    Ok(Box::new(JmnedictDictionary::from_bytes(&data)?))
+}
+
+/// Get the kind of name from JMnedict name types.
+fn name_kind(tags: &[String]) -> &str {
+    for tag in tags {
+        match tag.as_str() {
+            "surname"
+            | "given"
+            | "masc"
+            | "fem"
+            | "person"
+            | "family or surname"
+            | "given name or forename, gender not specified"
+            | "male given name or forename"
+            | "female given name or forename"
+            | "full name of a particular person" => return "person",
+            "place" | "station" | "place name" | "railway station" => return "place",
+            _ => {}
+        }
+    }
+    match tags.first() {
+        Some(tag) => tag,
+        None => "unknown",
+    }
+}
+
+/// Embeded Japanese language data.
+#[derive(RustEmbed)]
+#[folder = "metadata/language/jp"]
+#[exclude = "*.tgz"]
+struct JapaneseMetadata;
+
+#[derive(Debug, Clone)]
+struct CommonVocab {
+    word: String,
+    reading: String,
+    meaning: String,
+    level: u8,
+}
+
+impl From<&CommonVocab> for DictLookup {
+    fn from(value: &CommonVocab) -> Self {
+        Self {
+            is_kana: !value.word.is_kana(),
+            kana: value.reading.clone(),
+            meaning: value.meaning.clone(),
+            jlpt: value.level.into(),
+            source: Some(LookupSource::Common),
+            ..Default::default()
+        }
+    }
+}
+
+/// Serde derive class for the jmdict-simplified json format.
+#[derive(Debug, serde::Deserialize)]
+struct JmdictFile {
+    words: Vec<JmdictWord>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct JmdictWord {
+    #[serde(default)]
+    kanji: Vec<JmdictText>,
+    #[serde(default)]
+    kana: Vec<JmdictText>,
+    #[serde(default)]
+    sense: Vec<JmdictSense>,
+}
+
+impl JmdictWord {
+    /// Get the first meaning that applies to a reading.
+    fn gloss(&self, kana: &str) -> Option<String> {
+        self.sense
+            .iter()
+            .filter(|sense| applies_to(&sense.applies_to_kana, kana))
+            .find_map(|sense| sense.gloss.first())
+            .map(|gloss| gloss.text.clone())
+    }
+
+    /// Check if a reading has a priority tag.
+    fn is_common(&self, kana: &str) -> bool {
+        self.kana
+            .iter()
+            .any(|text| text.text == kana && text.common)
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct JmdictText {
+    text: String,
+    /// Whether the text has a priority tag, e.g. from newspapers.
+    #[serde(default)]
+    common: bool,
+    /// Kanji spellings a reading applies to, `*` for all.
+    #[serde(default, rename = "appliesToKanji")]
+    applies_to_kanji: Vec<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct JmdictSense {
+    #[serde(default)]
+    gloss: Vec<JmdictText>,
+    /// Readings a sense applies to, `*` for all.
+    #[serde(default, rename = "appliesToKana")]
+    applies_to_kana: Vec<String>,
+}
+
+/// Check if a jmdict-simplified restriction list, e.g. `appliesToKana`,
+/// allows a text.
+fn applies_to(restrictions: &[String], text: &str) -> bool {
+    restrictions.is_empty()
+        || restrictions
+            .iter()
+            .any(|allowed| allowed == "*" || allowed == text)
+}
+
+/// Serde derive class for the CSV format of JLPT vocabulary.
+#[derive(Debug, serde::Deserialize)]
+#[allow(unused)]
+struct JlptEntry {
+    expression: String,
+    reading: String,
+    meaning: String,
+    tags: String,
+    guid: String,
+    #[serde(default)]
+    level: u8,
+}
+
+impl From<JlptEntry> for CommonVocab {
+    fn from(value: JlptEntry) -> Self {
+        Self {
+            word: value.expression,
+            reading: value.reading,
+            meaning: value.meaning,
+            level: value.level,
+        }
+    }
+}
+
+/// Serde derive class for the CSV format of kore 6k.
+#[derive(Debug, serde::Deserialize)]
+#[allow(unused)]
+struct KoreEntry {
+    #[serde(alias = "Vocab-expression")]
+    expression: String,
+    #[serde(alias = "Core-index")]
+    core_index: usize,
+    #[serde(alias = "Vocab-meaning")]
+    meaning: String,
+    #[serde(alias = "Vocab-kana")]
+    reading: String,
+    #[serde(alias = "jlpt ", alias = "jlpt")]
+    _jlpt: String,
+}
+
+impl KoreEntry {
+    fn level(&self) -> u8 {
+        match self._jlpt.chars().last().unwrap_or('0') {
+            '0' => 1,
+            '1' => 2,
+            '2' => 3,
+            '3' => 4,
+            '4' => 5,
+            _ => 1,
+        }
+    }
+}
+
+impl From<KoreEntry> for CommonVocab {
+    fn from(value: KoreEntry) -> Self {
+        let level = value.level();
+        Self {
+            word: value.expression,
+            reading: value.reading,
+            meaning: value.meaning,
+            level,
+        }
+    }
+}
+
+trait JapaneseText<T: AsRef<str> = Self>: AsRef<str> {
+    #[allow(unused)]
+    fn is_english(&self) -> bool {
+        !self.is_kana() && !self.is_kanji()
+    }
+
+    fn is_kana(&self) -> bool {
+        use wana_kana::IsJapaneseStr;
+
+        self.as_ref().is_japanese() && !self.is_kanji()
+    }
+
+    fn is_kanji(&self) -> bool {
+        use wana_kana::IsJapaneseStr;
+
+        self.as_ref().contains_kanji()
+    }
+
+    fn character_count(&self) -> usize {
+        self.as_ref().trim().chars().count()
+    }
+}
+
+impl JapaneseText for &str {}
+
+impl JapaneseText for String {}
@@ -0,0 +1,200 @@
+//! Kanji information from KANJIDIC2.
+
+use super::*;
+
+use quick_xml::events::Event;
+
+/// Kanji dictionary, loaded from KANJIDIC2.
+#[derive(Debug, Clone, Default)]
+pub struct Kanjidic {
+    kanji: HashMap<char, KanjiInfo>,
+}
+
+impl Kanjidic {
+    /// Load a KANJIDIC2 xml file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => bail!("Unable to read KANJIDIC2 '{}': {e}", path.to_string_lossy()),
+        };
+        Self::from_xml(&content)
+    }
+
+    /// Parse KANJIDIC2 xml.
+    pub fn from_xml(xml: &str) -> Result<Self> {
+        let mut reader = quick_xml::Reader::from_str(xml);
+        reader.config_mut().trim_text(true);
+
+        let mut kanjidic = Self::default();
+        let mut current: Option<KanjiInfo> = None;
+        let mut field: Option<KanjiField> = None;
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(element)) => {
+                    let attribute = |name: &str| {
+                        element
+                            .try_get_attribute(name)
+                            .ok()
+                            .flatten()
+                            .map(|attribute| attribute.value.into_owned())
+                    };
+                    field = match element.name().as_ref() {
+                        b"character" => {
+                            current = Some(KanjiInfo::default());
+                            None
+                        }
+                        b"literal" => Some(KanjiField::Literal),
+                        b"stroke_count" => Some(KanjiField::Strokes),
+                        b"grade" => Some(KanjiField::Grade),
+                        b"jlpt" => Some(KanjiField::Jlpt),
+                        b"reading" => match attribute("r_type").as_deref() {
+                            Some(b"ja_on") => Some(KanjiField::On),
+                            Some(b"ja_kun") => Some(KanjiField::Kun),
+                            _ => None,
+                        },
+                        // Meanings in other languages have a language code.
+                        b"meaning" => match attribute("m_lang") {
+                            None => Some(KanjiField::Meaning),
+                            Some(_) => None,
+                        },
+                        _ => None,
+                    };
+                }
+                Ok(Event::Text(text)) => {
+                    let (Some(kanji), Some(field)) = (current.as_mut(), field) else {
+                        continue;
+                    };
+                    let text = match text.unescape() {
+                        Ok(text) => text.into_owned(),
+                        Err(e) => bail!("Unable to parse KANJIDIC2 text: {e}"),
+                    };
+                    kanji.set(field, text);
+                }
+                Ok(Event::End(element)) => {
+                    field = None;
+                    if element.name().as_ref() == b"character" {
+                        if let Some(kanji) = current.take() {
+                            kanjidic.kanji.insert(kanji.literal, kanji);
+                        }
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Ok(_) => {}
+                Err(e) => bail!(
+                    "Unable to parse KANJIDIC2 at position {}: {e}",
+                    reader.error_position()
+                ),
+            }
+        }
+
+        tracing::debug!("Loaded {} KANJIDIC2 entries.", kanjidic.kanji.len());
+
+        Ok(kanjidic)
+    }
+
+    /// Get a kanji.
+    pub fn get(&self, kanji: char) -> Option<&KanjiInfo> {
+        self.kanji.get(&kanji)
+    }
+
+    /// Get each known kanji in a word, in order.
+    pub fn word(&self, word: &str) -> Vec<KanjiInfo> {
+        word.chars()
+            .filter_map(|kanji| self.get(kanji))
+            .cloned()
+            .collect()
+    }
+}
+
+/// KANJIDIC2 entry.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct KanjiInfo {
+    /// The kanji.
+    pub literal: char,
+    /// English meanings.
+    pub meanings: Vec<String>,
+    /// On readings, in katakana.
+    pub on: Vec<String>,
+    /// Kun readings, in hiragana.
+    pub kun: Vec<String>,
+    /// Stroke count.
+    pub strokes: u8,
+    /// School grade: 1 to 6 for the kyōiku grades, 8 for the remaining jōyō
+    /// kanji and 9 or 10 for jinmeiyō kanji.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grade: Option<u8>,
+    /// Level from the old four level JLPT, 4 being the easiest.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jlpt: Option<u8>,
+}
+
+impl KanjiInfo {
+    /// Check if the kanji is above a grade. Kanji without a grade are above
+    /// every grade.
+    pub fn above_grade(&self, grade: u8) -> bool {
+        self.grade.is_none_or(|kanji_grade| kanji_grade > grade)
+    }
+
+    fn set(&mut self, field: KanjiField, text: String) {
+        match field {
+            KanjiField::Literal => {
+                if let Some(literal) = text.chars().next() {
+                    self.literal = literal;
+                }
+            }
+            // Only the first stroke count is correct, the rest are common
+            // miscounts.
+            KanjiField::Strokes if self.strokes == 0 => {
+                self.strokes = text.parse().unwrap_or_default();
+            }
+            KanjiField::Strokes => {}
+            KanjiField::Grade => self.grade = text.parse().ok(),
+            KanjiField::Jlpt => self.jlpt = text.parse().ok(),
+            KanjiField::On => self.on.push(text),
+            KanjiField::Kun => self.kun.push(text),
+            KanjiField::Meaning => self.meanings.push(text),
+        }
+    }
+}
+
+impl std::fmt::Display for KanjiInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Keep tooltips short, the first few are the most common.
+        let readings: Vec<&str> = self
+            .on
+            .iter()
+            .take(2)
+            .chain(self.kun.iter().take(2))
+            .map(String::as_str)
+            .collect();
+        let meanings: Vec<&str> = self.meanings.iter().take(3).map(String::as_str).collect();
+
+        write!(f, "{}", self.literal)?;
+        if !readings.is_empty() {
+            write!(f, " ({})", readings.join(", "))?;
+        }
+        write!(f, " {}; {} strokes", meanings.join(", "), self.strokes)?;
+        match self.grade {
+            Some(grade @ 1..=6) => write!(f, ", grade {grade}")?,
+            Some(8) => write!(f, ", jōyō")?,
+            Some(9 | 10) => write!(f, ", jinmeiyō")?,
+            _ => {}
+        }
+        if let Some(jlpt) = self.jlpt {
+            write!(f, ", JLPT {jlpt}")?;
+        }
+        Ok(())
+    }
+}
+
+/// KANJIDIC2 element being parsed.
+#[derive(Debug, Clone, Copy)]
+enum KanjiField {
+    Literal,
+    Strokes,
+    Grade,
+    Jlpt,
+    On,
+    Kun,
+    Meaning,
+}
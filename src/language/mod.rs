@@ -1,242 +1,197 @@
 use super::*;
 
-/// A dictionary database.
-/// Currently, this only supports Japanese.
-pub struct DictDb {
-    common: HashMap<String, CommonVocab>,
-    #[allow(unused)]
-    config: LanguageConfig,
-    #[cfg(feature = "jp")]
-    dict: jmdict_fast::Dict<'static>,
-}
+mod chinese;
+mod counters;
+mod deinflect;
+pub mod dictionary;
+mod glossary;
+mod japanese;
+mod kanji;
+mod pitch;
+mod tokenizer;
+
+pub use chinese::ChineseBackend;
+pub use counters::{counter_reading, parse_number};
+pub use deinflect::{Deinflection, deinflect};
+pub use dictionary::{DictEntry, Dictionary, FrequencyList, JmnedictDictionary, YomitanDictionary};
+pub use glossary::Glossary;
+pub use japanese::JapaneseBackend;
+pub use kanji::{KanjiInfo, Kanjidic};
+pub use pitch::{AnnotatedPitch, PitchAccents, morae, pitch_mark};
+#[cfg(any(feature = "lindera-ipadic", feature = "lindera-unidic"))]
+pub use tokenizer::LinderaTokenizer;
+pub use tokenizer::{CharabiaTokenizer, Tokenizer, japanese_tokenizer};
+
+use dictionary::Source;
+
+/// Language support for annotating text. Each backend tokenizes text, looks
+/// up words and decides which words are difficult enough to annotate.
+pub trait LanguageBackend: Send + Sync {
+    /// Split text into tokens.
+    fn tokenize(&self, text: &str) -> Vec<Token>;
 
-impl DictDb {
-    /// Create a new DictDb.
-    pub fn new(config: &Config) -> Result<Self> {
-        #[cfg(feature = "jp")]
-        let dict = jmdict_fast::Dict::load_default()?;
-
-        let mut db = Self {
-            common: HashMap::new(),
-            #[cfg(feature = "jp")]
-            dict,
-            config: config.language.clone(),
-        };
-
-        db.read_kore_csv("kore_6k/kore.csv")?;
-
-        db.read_jlpt_csv("jlpt/n1.csv", 1)?;
-        db.read_jlpt_csv("jlpt/n2.csv", 2)?;
-        db.read_jlpt_csv("jlpt/n3.csv", 3)?;
-        db.read_jlpt_csv("jlpt/n4.csv", 4)?;
-        db.read_jlpt_csv("jlpt/n5.csv", 5)?;
-
-        tracing::debug!("Parsed {} JLPT entries.", db.common.len());
+    /// Lookup word.
+    fn lookup(&self, word: &str) -> Option<DictLookup>;
 
-        Ok(db)
-    }
+    /// Check which annotations a word needs at the configured difficulty
+    /// levels.
+    fn difficulty(&self, word: &str, lookup: &DictLookup, config: &LanguageConfig) -> Difficulty;
 
-    /// Read a jlpt csv.
-    fn read_jlpt_csv(&mut self, file: &str, level: u8) -> Result<()> {
-        let content = read_embedded_text::<JapaneseMetadata>(file)?;
-        // let file = std::fs::File::open(file)?;
-        // let mut reader = csv::ReaderBuilder::new().from_reader(std::io::BufReader::new(file));
-        let mut reader =
-            csv::ReaderBuilder::new().from_reader(std::io::BufReader::new(content.as_bytes()));
-        // let mut reader = csv::ReaderBuilder::new().from_reader(&content);
-        for result in reader.deserialize() {
-            // Notice that we need to provide a type hint for automatic
-            // deserialization.
-            let mut record: JlptEntry = match result {
-                Ok(record) => record,
-                Err(e) => {
-                    tracing::warn!("Unable to parse JlptEntry: {e}");
-                    continue;
-                }
-            };
-            record.level = level;
-            self.add_common(record.into());
+    /// Reading annotation shown above a word, e.g. furigana.
+    fn reading(&self, #[allow(unused)] word: &str, lookup: &DictLookup) -> Option<String> {
+        match lookup.kana.is_empty() {
+            true => None,
+            false => Some(lookup.kana.clone()),
         }
-
-        Ok(())
     }
 
-    /// Read the kore 6k csv.
-    fn read_kore_csv(&mut self, file: &str) -> Result<()> {
-        let content = read_embedded_text::<JapaneseMetadata>(file)?;
-        let mut reader =
-            csv::ReaderBuilder::new().from_reader(std::io::BufReader::new(content.as_bytes()));
-        for result in reader.deserialize() {
-            let record: KoreEntry = match result {
-                Ok(record) => record,
-                Err(e) => {
-                    tracing::warn!("Unable to parse KoreEntry: {e}");
-                    continue;
-                }
-            };
-            self.add_common(record.into());
-        }
+    /// Format a lookup as a tooltip for a word.
+    fn tooltip(&self, word: &str, lookup: &DictLookup) -> String {
+        lookup.tooltip(word)
+    }
 
-        Ok(())
+    /// Lookup a proper name.
+    fn lookup_name(&self, #[allow(unused)] word: &str) -> Option<DictLookup> {
+        None
     }
 
-    /// Add common word.
-    fn add_common(&mut self, common: CommonVocab) -> bool {
-        // Don't add single-character hiragana.
-        if common.word.is_kana() && common.word.character_count() == 1 {
-            return false;
-        }
+    /// Check if a dictionary has an exact entry for a word, to find set
+    /// phrases and compounds split by the tokenizer.
+    fn contains(&self, #[allow(unused)] word: &str) -> bool {
+        false
+    }
 
-        // Otherwise insert both the word and its reading.
-        if common.word != common.reading {
-            self.common.insert(common.word.clone(), common.clone());
-        }
-        self.common.insert(common.reading.clone(), common);
-        true
+    /// Lookup a word no dictionary knows from its characters, e.g. kanji.
+    fn lookup_characters(&self, #[allow(unused)] word: &str) -> Option<DictLookup> {
+        None
     }
 
-    /// Lookup word.
-    pub fn lookup(&self, word: &str) -> Option<DictLookup> {
-        let word = word.trim();
+    /// Check if a token marks the previous word as a name, e.g. an honorific.
+    fn is_name_suffix(&self, #[allow(unused)] token: &Token) -> bool {
+        false
+    }
 
-        // Skip english words, numerals, etc.
-        if word.is_english() {
-            return None;
+    /// Lookup a token in context. Configured names come first, then names
+    /// followed by a name suffix, then dictionary words, other names and
+    /// finally the token's characters. Tokens with a base form are looked up
+    /// by it, keeping the reading of the text as written.
+    fn lookup_token(
+        &self,
+        token: &Token,
+        previous: Option<&Token>,
+        next: Option<&Token>,
+        config: &Config,
+    ) -> Option<DictLookup> {
+        let word = token.text.as_str();
+        if let Some(name) = config.language.names.iter().find(|name| name.name == word) {
+            let mut lookup =
+                DictLookup::name(&name.reading, name.kind.as_deref().unwrap_or("person"), "");
+            lookup.source = Some(LookupSource::ConfigName);
+            return Some(lookup);
         }
-
-        // If a common word, just use that.
-        if let Some(common) = self.common.get(word) {
-            return Some(common.into());
+        if next.is_some_and(|next| self.is_name_suffix(next)) {
+            if let Some(lookup) = self.lookup_name(word) {
+                return Some(lookup);
+            }
         }
-
-        #[cfg(feature = "jp")]
-        {
-            // Otherwise we look the word up.
-            let results = match self.config.approximate {
-                false => self.dict.lookup_exact(word),
-                true => {
-                    let deinflected = self.dict.lookup_exact_with_deinflection(word);
-                    if deinflected.len() > 0 {
-                        // If a deinflected word is common, we'll use that.
-                        for entry in deinflected.iter() {
-                            for kanji in entry.kanji.iter() {
-                                if let Some(common) = self.common.get(&kanji.text) {
-                                    return Some(common.into());
-                                }
-                            }
-                            for kana in entry.kana.iter() {
-                                if let Some(common) = self.common.get(&kana.text) {
-                                    return Some(common.into());
-                                }
-                            }
-                        }
-
-                        // Otherwise, we use teh deinflected word.
-                        deinflected
-                    } else {
-                        self.dict.lookup_partial(word)
-                    }
+        if let Some(base) = token.base.as_deref().filter(|base| *base != word) {
+            if let Some(mut lookup) = self.lookup(base) {
+                if let Some(reading) = &token.reading {
+                    lookup.kana = reading.clone();
                 }
-            };
-            if results.len() == 0 {
-                return None;
+                return Some(lookup);
             }
+        }
+        let mut lookup = self
+            .lookup(word)
+            .or_else(|| self.lookup_name(word))
+            .or_else(|| self.lookup_characters(word))?;
+        if lookup.readings.len() > 1 {
+            self.choose_reading(token, previous, next, &mut lookup);
+        }
+        Some(lookup)
+    }
 
-            if results[0].kana.len() == 0
-                || results[0].sense.len() == 0
-                || results[0].sense[0].gloss.len() == 0
-            {
-                return None;
-            }
+    /// Choose between the readings of a word with more than one, from the
+    /// neighbouring tokens. The most common reading is kept by default.
+    fn choose_reading(
+        &self,
+        #[allow(unused)] token: &Token,
+        #[allow(unused)] previous: Option<&Token>,
+        #[allow(unused)] next: Option<&Token>,
+        #[allow(unused)] lookup: &mut DictLookup,
+    ) {
+    }
 
-            return Some(DictLookup {
-                is_kana: word.trim().is_kana(),
-                kana: results[0].kana[0].text.clone(),
-                meaning: results[0].sense[0].gloss[0].text.clone(),
-                jlpt: JlptLevel::None,
+    /// Split text into tokens and look up each word in context, with the
+    /// attributes of the words that get annotated.
+    fn annotate_tokens(&self, text: &str, config: &Config) -> Vec<AnnotatedToken> {
+        let tokens = self.merge_tokens(self.tokenize(text), config.language.max_span());
+        let mut annotated = Vec::new();
+        for (i, token) in tokens.iter().enumerate() {
+            let lookup = match token.is_word {
+                true => self.lookup_token(
+                    token,
+                    i.checked_sub(1).and_then(|i| tokens.get(i)),
+                    tokens.get(i + 1),
+                    config,
+                ),
+                false => None,
+            };
+            let attributes = lookup
+                .as_ref()
+                .and_then(|lookup| self.attributes(token, lookup, config));
+            annotated.push(AnnotatedToken {
+                token: token.clone(),
+                lookup,
+                attributes,
             });
         }
-
-        #[cfg(not(feature = "jp"))]
-        None
+        annotated
     }
 
-    /// Transform a durf AST to one annotated with DictDb lookups.
-    pub fn transform(&self, node: &mut durf::RawNode, config: &Config) -> Result<()> {
+    /// Transform a durf AST to one annotated with lookups, keeping the pitch
+    /// accents of the annotated readings.
+    fn transform(
+        &self,
+        node: &mut durf::RawNode,
+        config: &Config,
+        pitch: &mut AnnotatedPitch,
+    ) -> Result<()> {
         match node {
             durf_parser::RawNode::Empty => {}
             durf_parser::RawNode::Section(section) => {
                 for node in section.nodes.iter_mut() {
-                    self.transform(node, config)?;
+                    self.transform(node, config, pitch)?;
                 }
             }
             durf_parser::RawNode::Text(text) => {
                 let mut new_text = durf_parser::Text::new();
-
-                // TODO: Configurable skip characters.
-                let total_text = text
-                    .fragments
-                    .iter()
-                    .fold(String::new(), |acc, el| acc + &el.text)
-                    .replace("\n", " ")
-                    .replace("　", "");
-                let total_text = total_text.as_str();
-
-                let tokens = total_text.tokenize();
-                'token_loop: for token in tokens {
-                    if token.is_word() {
-                        let word = token.lemma();
-                        if let Some(lookup) = self.lookup(word) {
-                            // TODO: Support keeping the previous text attributes.
-                            // If an existing annotation exists, prefer that (e.g.,
-                            // name readings).
-
-                            // If this is a single character kana, skip.
-                            // TODO: This should be smarter. We should check for
-                            // things like counters.
-                            if lookup.is_kana
-                                && (word.character_count() == 1
-                                    || lookup.kana.character_count() == 1)
-                            {
-                                new_text
-                                    .fragments
-                                    .push(durf_parser::TextFragment::new(token.lemma(), None));
-                                continue 'token_loop;
-                            }
-
-                            // If the jlpt level of this word is higher than our
-                            // jlpt level, skip.
-                            if lookup.jlpt > config.language.japanese.lowest_level() {
-                                new_text
-                                    .fragments
-                                    .push(durf_parser::TextFragment::new(token.lemma(), None));
-                                continue 'token_loop;
-                            }
-
-                            // Add appropriate attributes.
-                            let mut attributes = durf_parser::TextAttributes::default();
-                            if lookup.jlpt <= config.language.japanese.definitions() {
-                                attributes.tooltip = Some(format!(
-                                    "{}[{}::{}::{}]",
-                                    word, lookup.kana, lookup.meaning, lookup.jlpt,
-                                ));
-                            }
-                            if lookup.jlpt <= config.language.japanese.furigana() {
-                                attributes.annotation = Some(lookup.kana);
-                            }
-                            new_text.fragments.push(durf_parser::TextFragment::new(
-                                token.lemma(),
-                                Some(attributes),
-                            ));
-                        } else {
-                            new_text
-                                .fragments
-                                .push(durf_parser::TextFragment::new(token.lemma(), None));
+                let heading = text_heading(text);
+
+                let total_text = annotated_text(text);
+
+                // TODO: Support keeping the previous text attributes.
+                // If an existing annotation exists, prefer that (e.g.,
+                // name readings).
+                for token in self.annotate_tokens(&total_text, config) {
+                    if let (Some(attributes), Some(lookup)) = (&token.attributes, &token.lookup) {
+                        if let (Some(reading), Some(downstep)) =
+                            (&attributes.annotation, lookup.pitch.first())
+                        {
+                            pitch.insert(&token.token.text, reading, *downstep);
                         }
-                    } else {
-                        new_text
-                            .fragments
-                            .push(durf_parser::TextFragment::new(token.lemma(), None));
+                    }
+                    new_text.fragments.push(durf_parser::TextFragment::new(
+                        &token.token.text,
+                        token.attributes,
+                    ));
+                }
+
+                // Keep headings so they can still be used for navigation.
+                if let Some(heading) = heading {
+                    for fragment in new_text.fragments.iter_mut() {
+                        fragment.attributes.heading = Some(heading);
                     }
                 }
 
@@ -246,129 +201,456 @@ impl DictDb {
 
         Ok(())
     }
-}
 
-/// Dictionary lookup result.
-pub struct DictLookup {
-    pub is_kana: bool,
-    pub kana: String,
-    pub meaning: String,
-    pub jlpt: JlptLevel,
-}
+    /// Join runs of adjacent words with an exact dictionary entry, e.g. set
+    /// phrases and compounds, so they are annotated as one word. The longest
+    /// run of at most `max_span` tokens is used. Runs made only of particles,
+    /// e.g. か and も, are kept apart even when they spell a word like かも.
+    fn merge_tokens(&self, tokens: Vec<Token>, max_span: usize) -> Vec<Token> {
+        let mut merged = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            let mut end = i + 1;
+            for span in (2..=max_span.min(tokens.len() - i)).rev() {
+                let run = &tokens[i..i + span];
+                if !run.iter().all(|token| token.is_word) || run.iter().all(Token::is_particle) {
+                    continue;
+                }
+                let text: String = run.iter().map(|token| token.text.as_str()).collect();
+                if self.contains(&text) {
+                    end = i + span;
+                    break;
+                }
+            }
+            match end - i {
+                1 => merged.push(tokens[i].clone()),
+                _ => merged.push(Token::join(&tokens[i..end])),
+            }
+            i = end;
+        }
+        merged
+    }
+
+    /// Get the text attributes for a looked up token, if it should be
+    /// annotated.
+    fn attributes(
+        &self,
+        token: &Token,
+        lookup: &DictLookup,
+        config: &Config,
+    ) -> Option<durf_parser::TextAttributes> {
+        let word = token.text.as_str();
+        let difficulty = match lookup.name {
+            // Names are always annotated, since they can't be learned.
+            Some(_) => Difficulty {
+                definition: true,
+                reading: true,
+            },
+            None => self.difficulty(word, lookup, &config.language),
+        };
+        if !difficulty.definition && !difficulty.reading {
+            return None;
+        }
 
-/// Embeded Japanese language data.
-#[derive(RustEmbed)]
-#[folder = "metadata/language/jp"]
-#[exclude = "*.tgz"]
-struct JapaneseMetadata;
+        let mut attributes = durf_parser::TextAttributes::default();
+        if difficulty.definition {
+            attributes.tooltip = Some(self.tooltip(word, lookup));
+        }
+        if difficulty.reading {
+            attributes.annotation = self.reading(word, lookup);
+        }
+        Some(attributes)
+    }
+}
 
-#[derive(Debug, Clone)]
-struct CommonVocab {
-    word: String,
-    reading: String,
-    meaning: String,
-    level: u8,
+/// Token produced by a [`LanguageBackend`].
+#[derive(Debug, Clone, Default)]
+pub struct Token {
+    /// Token text.
+    pub text: String,
+    /// Whether the token is a word, rather than punctuation or whitespace.
+    pub is_word: bool,
+    /// Dictionary form, e.g. 食べる for 食べた, if the tokenizer knows it.
+    pub base: Option<String>,
+    /// Reading of the text as written, if the tokenizer knows it.
+    pub reading: Option<String>,
+    /// Part of speech, if the tokenizer knows it.
+    pub pos: Option<String>,
 }
 
-impl From<&CommonVocab> for DictLookup {
-    fn from(value: &CommonVocab) -> Self {
+impl Token {
+    /// Join a run of tokens into one word. The part of speech is the first
+    /// token's, and the reading and dictionary form are only kept when the
+    /// tokenizer knows them for the whole run.
+    fn join(run: &[Token]) -> Self {
+        let text: String = run.iter().map(|token| token.text.as_str()).collect();
+        let reading = run
+            .iter()
+            .map(|token| token.reading.as_deref())
+            .collect::<Option<String>>();
+        // Only the last token of the run may be inflected.
+        let base = run.split_last().and_then(|(last, head)| {
+            let base = last.base.as_deref().filter(|base| *base != last.text)?;
+            let head: String = head.iter().map(|token| token.text.as_str()).collect();
+            Some(head + base)
+        });
         Self {
-            is_kana: !value.word.is_kana(),
-            kana: value.reading.clone(),
-            meaning: value.meaning.clone(),
-            jlpt: value.level.into(),
+            text,
+            is_word: true,
+            base,
+            reading,
+            pos: run.first().and_then(|token| token.pos.clone()),
+        }
+    }
+
+    /// Whether the token is a particle, from its part of speech or, without
+    /// one, from being a single hiragana.
+    fn is_particle(&self) -> bool {
+        use wana_kana::IsJapaneseStr;
+
+        match &self.pos {
+            Some(pos) => pos.starts_with("助詞"),
+            None => self.text.chars().count() == 1 && self.text.as_str().is_hiragana(),
         }
     }
 }
 
-/// Serde derive class for the CSV format of JLPT vocabulary.
-#[derive(Debug, serde::Deserialize)]
-#[allow(unused)]
-struct JlptEntry {
-    expression: String,
-    reading: String,
-    meaning: String,
-    tags: String,
-    guid: String,
-    #[serde(default)]
-    level: u8,
+/// Token looked up in context by [`LanguageBackend::annotate_tokens`].
+#[derive(Debug, Clone)]
+pub struct AnnotatedToken {
+    /// The token.
+    pub token: Token,
+    /// Lookup used to annotate the token, if any source knows it.
+    pub lookup: Option<DictLookup>,
+    /// Text attributes, if the token is annotated.
+    pub attributes: Option<durf_parser::TextAttributes>,
+}
+
+/// Annotations a word needs at the configured difficulty levels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Difficulty {
+    /// Whether the word gets a definition tooltip.
+    pub definition: bool,
+    /// Whether the word gets a reading annotation.
+    pub reading: bool,
 }
 
-impl From<JlptEntry> for CommonVocab {
-    fn from(value: JlptEntry) -> Self {
+impl Difficulty {
+    /// Combine a difficulty from levels with the word's frequency rank.
+    pub fn with_frequency(
+        self,
+        has_level: bool,
+        frequency: Option<u64>,
+        config: &FrequencyConfig,
+    ) -> Self {
+        let combine = |difficult: bool, threshold: Option<u64>| {
+            let Some(threshold) = threshold else {
+                return difficult;
+            };
+            // Words missing from the frequency lists are rare.
+            let rare = frequency.is_none_or(|rank| rank > threshold);
+            match config.mode() {
+                FrequencyMode::Fallback if has_level => difficult,
+                FrequencyMode::Fallback | FrequencyMode::Replace => rare,
+                FrequencyMode::Both => difficult && rare,
+            }
+        };
         Self {
-            word: value.expression,
-            reading: value.reading,
-            meaning: value.meaning,
-            level: value.level,
+            definition: combine(self.definition, config.definitions),
+            reading: combine(self.reading, config.readings),
         }
     }
 }
 
-/// Serde derive class for the CSV format of kore 6k.
-#[derive(Debug, serde::Deserialize)]
-#[allow(unused)]
-struct KoreEntry {
-    #[serde(alias = "Vocab-expression")]
-    expression: String,
-    #[serde(alias = "Core-index")]
-    core_index: usize,
-    #[serde(alias = "Vocab-meaning")]
-    meaning: String,
-    #[serde(alias = "Vocab-kana")]
-    reading: String,
-    #[serde(alias = "jlpt ", alias = "jlpt")]
-    _jlpt: String,
+/// A dictionary database, with a backend for each language.
+#[derive(Default)]
+pub struct DictDb {
+    backends: HashMap<Language, Box<dyn LanguageBackend>>,
 }
 
-impl KoreEntry {
-    fn level(&self) -> u8 {
-        match self._jlpt.chars().last().unwrap_or('0') {
-            '0' => 1,
-            '1' => 2,
-            '2' => 3,
-            '3' => 4,
-            '4' => 5,
-            _ => 1,
+impl DictDb {
+    /// Create a new DictDb with the built-in backends.
+    pub fn new(config: &Config) -> Result<Self> {
+        Self::with_japanese(config, JapaneseBackend::new(config)?)
+    }
+
+    /// Create a new DictDb with the built-in backends, using an already
+    /// loaded Japanese backend, e.g. with an additional dictionary.
+    pub fn with_japanese(config: &Config, japanese: JapaneseBackend) -> Result<Self> {
+        let mut db = Self::default();
+        db.add_backend(Language::Japanese, japanese);
+        db.add_backend(Language::Chinese, ChineseBackend::new(config)?);
+
+        Ok(db)
+    }
+
+    /// Add a backend, replacing any existing backend for the language.
+    pub fn add_backend(&mut self, language: Language, backend: impl LanguageBackend + 'static) {
+        self.backends.insert(language, Box::new(backend));
+    }
+
+    /// Get the backend for a language.
+    pub fn backend(&self, language: Language) -> Result<&dyn LanguageBackend> {
+        match self.backends.get(&language) {
+            Some(backend) => Ok(backend.as_ref()),
+            None => bail!("{language} is not supported."),
+        }
+    }
+
+    /// Lookup word or name in a language, falling back to the word's
+    /// characters.
+    pub fn lookup(&self, word: &str, language: Language) -> Option<DictLookup> {
+        let backend = self.backends.get(&language)?;
+        backend
+            .lookup(word)
+            .or_else(|| backend.lookup_name(word))
+            .or_else(|| backend.lookup_characters(word))
+    }
+
+    /// Transform a durf AST in the configured language, returning the pitch
+    /// accents of the annotated readings.
+    pub fn transform(&self, node: &mut durf::RawNode, config: &Config) -> Result<AnnotatedPitch> {
+        let mut pitch = AnnotatedPitch::default();
+        self.backend(config.language.language())?
+            .transform(node, config, &mut pitch)?;
+        Ok(pitch)
+    }
+
+    /// Annotate a chapter, using the chapter's language if set.
+    pub fn annotate(&self, chapter: &mut Chapter, config: &Config) -> Result<()> {
+        let language = chapter.language.unwrap_or(config.language.language());
+        self.backend(language)?
+            .transform(&mut chapter.ast.root, config, &mut chapter.pitch)
+    }
+
+    /// Split text into tokens in the configured language and look up each
+    /// word the same way [`DictDb::transform`] does.
+    pub fn annotate_text(&self, text: &str, config: &Config) -> Result<Vec<AnnotatedToken>> {
+        Ok(self
+            .backend(config.language.language())?
+            .annotate_tokens(text, config))
+    }
+
+    /// Explain how each word of some text is looked up and annotated in the
+    /// configured language, the same way [`DictDb::transform`] does.
+    pub fn explain(&self, text: &str, config: &Config) -> Result<Vec<TokenReport>> {
+        let mut reports = Vec::new();
+        for AnnotatedToken {
+            token,
+            lookup,
+            attributes,
+        } in self.annotate_text(text, config)?
+        {
+            if !token.is_word {
+                continue;
+            }
+            reports.push(TokenReport {
+                surface: token.text,
+                lemma: token.base.or_else(|| {
+                    let deinflection = lookup.as_ref()?.deinflection.as_ref()?;
+                    Some(deinflection.base.clone())
+                }),
+                lookup,
+                furigana: attributes
+                    .as_ref()
+                    .and_then(|attributes| attributes.annotation.clone()),
+                tooltip: attributes.and_then(|attributes| attributes.tooltip),
+            });
         }
+        Ok(reports)
     }
 }
 
-impl From<KoreEntry> for CommonVocab {
-    fn from(value: KoreEntry) -> Self {
-        let level = value.level();
+/// How a token is looked up and annotated.
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenReport {
+    /// Token text.
+    pub surface: String,
+    /// Dictionary form, from the tokenizer or deinflection.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lemma: Option<String>,
+    /// Lookup of the token, if any source knows it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lookup: Option<DictLookup>,
+    /// Reading shown above the token, e.g. furigana or pinyin, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub furigana: Option<String>,
+    /// Tooltip, if the token would get one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tooltip: Option<String>,
+}
+
+/// Dictionary lookup result.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DictLookup {
+    /// Whether the word is written in kana.
+    pub is_kana: bool,
+    /// Reading of the word, e.g. kana or pinyin.
+    pub kana: String,
+    /// Meaning of the word.
+    pub meaning: String,
+    /// JLPT level of the word.
+    pub jlpt: JlptLevel,
+    /// HSK level of the word, for Chinese.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hsk: Option<HskLevel>,
+    /// Tags of the entry, e.g. parts of speech.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Frequency rank of the word, lower being more frequent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency: Option<u64>,
+    /// Kind of name, e.g. person or place, if the word is a proper name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Kanji in the word, if KANJIDIC2 is loaded.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub kanji: Vec<KanjiInfo>,
+    /// Pitch accent downsteps of the reading, if pitch accents are loaded.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pitch: Vec<u8>,
+    /// Conjugations from the dictionary form, if the word was deinflected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deinflection: Option<Deinflection>,
+    /// Readings of the word with their meanings, most common first, if it
+    /// has more than one.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub readings: Vec<Reading>,
+    /// Where the lookup was found.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<LookupSource>,
+}
+
+impl Default for DictLookup {
+    fn default() -> Self {
         Self {
-            word: value.expression,
-            reading: value.reading,
-            meaning: value.meaning,
-            level,
+            is_kana: false,
+            kana: String::new(),
+            meaning: String::new(),
+            jlpt: JlptLevel::None,
+            hsk: None,
+            tags: Vec::new(),
+            frequency: None,
+            name: None,
+            kanji: Vec::new(),
+            pitch: Vec::new(),
+            deinflection: None,
+            readings: Vec::new(),
+            source: None,
         }
     }
 }
 
-trait JapaneseText<T: AsRef<str> = Self>: AsRef<str> {
-    #[allow(unused)]
-    fn is_english(&self) -> bool {
-        !self.is_kana() && !self.is_kanji()
+impl DictLookup {
+    /// Create a lookup for a proper name, with an optional translation.
+    pub fn name(reading: &str, kind: &str, translation: &str) -> Self {
+        let meaning = match translation.is_empty() {
+            true => format!("name ({kind})"),
+            false => format!("{translation}, name ({kind})"),
+        };
+        Self {
+            kana: reading.to_string(),
+            meaning,
+            name: Some(kind.to_string()),
+            source: Some(LookupSource::Names),
+            ..Default::default()
+        }
     }
 
-    fn is_kana(&self) -> bool {
-        use wana_kana::IsJapaneseStr;
+    /// Format the lookup as a tooltip for a word.
+    pub fn tooltip(&self, word: &str) -> String {
+        format!("{}[{}::{}::{}]", word, self.kana, self.meaning, self.jlpt)
+    }
 
-        self.as_ref().is_japanese() && !self.is_kanji()
+    /// Switch to one of the word's readings, along with its meaning. Returns
+    /// false if the word has no such reading.
+    pub fn select_reading(&mut self, kana: &str) -> bool {
+        // Exact readings win over lists of readings, e.g. なん over なん; なに.
+        let Some(reading) = self
+            .readings
+            .iter()
+            .find(|reading| reading.kana == kana)
+            .or_else(|| {
+                self.readings.iter().find(|reading| {
+                    reading
+                        .kana
+                        .split("; ")
+                        .any(|alternative| alternative == kana)
+                })
+            })
+            .cloned()
+        else {
+            return false;
+        };
+        self.kana = reading.kana;
+        if !reading.meaning.is_empty() {
+            self.meaning = reading.meaning;
+        }
+        true
     }
+}
 
-    fn is_kanji(&self) -> bool {
-        use wana_kana::IsJapaneseStr;
+/// A reading of a word, with the meaning it has when read that way.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct Reading {
+    /// Reading, e.g. kana.
+    pub kana: String,
+    /// Meaning with this reading.
+    pub meaning: String,
+}
 
-        self.as_ref().contains_kanji()
-    }
+/// Where a lookup was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum LookupSource {
+    /// The configured glossary.
+    Glossary,
+    /// The configured names.
+    ConfigName,
+    /// A names dictionary.
+    Names,
+    /// The vocabulary lists, e.g. JLPT or HSK.
+    Common,
+    /// A dictionary file, e.g. jmdict-simplified or CC-CEDICT.
+    Dictionary,
+    /// An exact jmdict entry.
+    Jmdict,
+    /// A jmdict entry of the dictionary form of a conjugated word.
+    JmdictDeinflected,
+    /// A jmdict entry partially matching the word.
+    JmdictPartial,
+    /// An additional dictionary, e.g. a Yomitan dictionary.
+    External,
+    /// A number followed by a counter.
+    Counter,
+    /// The characters of the word, e.g. kanji.
+    Characters,
+}
 
-    fn character_count(&self) -> usize {
-        self.as_ref().trim().chars().count()
+impl std::fmt::Display for LookupSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                Self::Glossary => "glossary",
+                Self::ConfigName => "configured name",
+                Self::Names => "names",
+                Self::Common => "common",
+                Self::Dictionary => "dictionary",
+                Self::Jmdict => "jmdict exact",
+                Self::JmdictDeinflected => "jmdict deinflected",
+                Self::JmdictPartial => "jmdict partial",
+                Self::External => "external",
+                Self::Counter => "counter",
+                Self::Characters => "characters",
+            },
+        )
     }
 }
 
-impl JapaneseText for &str {}
-
-impl JapaneseText for String {}
+/// Get the JLPT level from a tooltip created by [`DictLookup::tooltip`].
+pub fn tooltip_level(tooltip: &str) -> Option<JlptLevel> {
+    let level = tooltip.strip_suffix(']')?.rsplit("::").next()?;
+    level.parse().ok()
+}
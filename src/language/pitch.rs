@@ -0,0 +1,122 @@
+//! Pitch accent data, e.g. from Kanjium.
+
+use super::*;
+
+use wana_kana::ConvertJapanese;
+
+/// Pitch accents by word and reading, as downstep positions. A downstep of 0
+/// is flat (heiban), otherwise the pitch drops after that mora.
+#[derive(Debug, Clone, Default)]
+pub struct PitchAccents {
+    accents: HashMap<(String, String), Vec<u8>>,
+}
+
+impl PitchAccents {
+    /// Load a Kanjium-style accents tsv.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => bail!(
+                "Unable to read pitch accents '{}': {e}",
+                path.to_string_lossy()
+            ),
+        };
+        Ok(Self::parse(&content))
+    }
+
+    /// Parse a Kanjium-style accents tsv, with word, reading and comma
+    /// separated downsteps on each line, e.g. `箸\tはし\t1`. Downsteps may be
+    /// prefixed with a part of speech, e.g. `(名)0`.
+    pub fn parse(content: &str) -> Self {
+        let mut pitch = Self::default();
+        for line in content.lines() {
+            let mut columns = line.split('\t').map(str::trim);
+            let (Some(word), Some(reading), Some(accents)) =
+                (columns.next(), columns.next(), columns.next())
+            else {
+                continue;
+            };
+            let reading = match reading.is_empty() {
+                true => word,
+                false => reading,
+            };
+            let downsteps: Vec<u8> = accents
+                .split(',')
+                .filter_map(|accent| {
+                    accent
+                        .trim_start_matches(|c: char| !c.is_ascii_digit())
+                        .parse()
+                        .ok()
+                })
+                .collect();
+            if word.is_empty() || downsteps.is_empty() {
+                continue;
+            }
+            pitch.accents.insert(key(word, reading), downsteps);
+        }
+
+        tracing::debug!("Loaded {} pitch accents.", pitch.accents.len());
+
+        pitch
+    }
+
+    /// Get the downsteps of a word with a reading.
+    pub fn get(&self, word: &str, reading: &str) -> Option<&[u8]> {
+        let reading = match reading.is_empty() {
+            true => word,
+            false => reading,
+        };
+        self.accents.get(&key(word, reading)).map(Vec::as_slice)
+    }
+}
+
+/// Key accents by hiragana readings, so katakana readings still match.
+fn key(word: &str, reading: &str) -> (String, String) {
+    (word.to_string(), reading.to_hiragana())
+}
+
+/// Split a kana reading into morae. Small kana belong to the previous mora,
+/// while っ and ー are morae of their own.
+pub fn morae(reading: &str) -> Vec<String> {
+    let mut morae: Vec<String> = Vec::new();
+    for c in reading.chars() {
+        match morae.last_mut() {
+            Some(mora) if "ゃゅょぁぃぅぇぉゎャュョァィゥェォヮ".contains(c) => {
+                mora.push(c)
+            }
+            _ => morae.push(c.to_string()),
+        }
+    }
+    morae
+}
+
+/// Format a downstep as a circled number, e.g. ⓪ or ①.
+pub fn pitch_mark(downstep: u8) -> char {
+    match downstep {
+        0 => '⓪',
+        1..=20 => char::from_u32(0x2460 + downstep as u32 - 1).unwrap_or('?'),
+        _ => '?',
+    }
+}
+
+/// Downsteps of the annotated words of a document, by word and reading, so
+/// exports can mark the pitch accent of readings.
+#[derive(Debug, Clone, Default)]
+pub struct AnnotatedPitch {
+    downsteps: HashMap<(String, String), u8>,
+}
+
+impl AnnotatedPitch {
+    /// Add the downstep of a word annotated with a reading.
+    pub fn insert(&mut self, word: &str, reading: &str, downstep: u8) {
+        self.downsteps
+            .insert((word.to_string(), reading.to_string()), downstep);
+    }
+
+    /// Get the downstep of a word annotated with a reading.
+    pub fn get(&self, word: &str, reading: &str) -> Option<u8> {
+        self.downsteps
+            .get(&(word.to_string(), reading.to_string()))
+            .copied()
+    }
+}
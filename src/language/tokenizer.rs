@@ -0,0 +1,204 @@
+//! Tokenizers for languages written without spaces between words.
+
+use super::*;
+
+/// Splits text into tokens, e.g. with a morphological analyzer.
+pub trait Tokenizer: Send + Sync {
+    /// Split text into tokens.
+    fn tokenize(&self, text: &str) -> Vec<Token>;
+}
+
+/// Create the configured Japanese tokenizer. The mode is only used by the
+/// Lindera tokenizers.
+pub fn japanese_tokenizer(
+    kind: JapaneseTokenizer,
+    #[allow(unused)] mode: LinderaMode,
+) -> Result<Box<dyn Tokenizer>> {
+    match kind {
+        JapaneseTokenizer::Charabia => Ok(Box::new(CharabiaTokenizer)),
+        #[cfg(feature = "lindera-ipadic")]
+        JapaneseTokenizer::LinderaIpadic => Ok(Box::new(LinderaTokenizer::ipadic(mode)?)),
+        #[cfg(feature = "lindera-unidic")]
+        JapaneseTokenizer::LinderaUnidic => Ok(Box::new(LinderaTokenizer::unidic(mode)?)),
+        #[allow(unreachable_patterns)]
+        _ => bail!("The {kind} tokenizer needs jdpub to be built with the {kind} feature."),
+    }
+}
+
+/// Tokenizer using charabia's default segmenter. Tokens are normalized text,
+/// without a base form or reading.
+pub struct CharabiaTokenizer;
+
+impl Tokenizer for CharabiaTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<Token> {
+        text.tokenize()
+            .map(|token| Token {
+                text: token.lemma().to_string(),
+                is_word: token.is_word(),
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+/// Tokenizer using Lindera's morphological analysis, which knows the base
+/// form, reading and part of speech of each token.
+#[cfg(any(feature = "lindera-ipadic", feature = "lindera-unidic"))]
+pub struct LinderaTokenizer {
+    tokenizer: lindera::tokenizer::Tokenizer,
+    /// Index of the base form in the token details.
+    base: usize,
+    /// Index of the reading in the token details.
+    reading: usize,
+    /// Index of the kana reading of the lexeme, for dictionaries whose
+    /// reading is the pronunciation, e.g. トーキョー for 東京.
+    lexeme_reading: Option<usize>,
+}
+
+#[cfg(any(feature = "lindera-ipadic", feature = "lindera-unidic"))]
+impl LinderaTokenizer {
+    /// Create a tokenizer with the IPADIC dictionary.
+    #[cfg(feature = "lindera-ipadic")]
+    pub fn ipadic(mode: LinderaMode) -> Result<Self> {
+        Self::new(
+            lindera::dictionary::DictionaryKind::IPADIC,
+            mode,
+            6,
+            7,
+            None,
+        )
+    }
+
+    /// Create a tokenizer with the UniDic dictionary.
+    #[cfg(feature = "lindera-unidic")]
+    pub fn unidic(mode: LinderaMode) -> Result<Self> {
+        // The written base form is used, since UniDic lemmas are often
+        // written differently, e.g. 為る for する. UniDic has no kana reading
+        // of the text as written, only its pronunciation.
+        Self::new(
+            lindera::dictionary::DictionaryKind::UniDic,
+            mode,
+            10,
+            9,
+            Some(6),
+        )
+    }
+
+    fn new(
+        kind: lindera::dictionary::DictionaryKind,
+        mode: LinderaMode,
+        base: usize,
+        reading: usize,
+        lexeme_reading: Option<usize>,
+    ) -> Result<Self> {
+        let dictionary = match lindera::dictionary::load_dictionary_from_kind(kind) {
+            Ok(dictionary) => dictionary,
+            Err(e) => bail!("Unable to load the Lindera dictionary: {e}"),
+        };
+        let mode = match mode {
+            LinderaMode::Normal => lindera::mode::Mode::Normal,
+            LinderaMode::Decompose => lindera::mode::Mode::Decompose(Default::default()),
+        };
+        let segmenter = lindera::segmenter::Segmenter::new(mode, dictionary, None);
+        Ok(Self {
+            tokenizer: lindera::tokenizer::Tokenizer::new(segmenter),
+            base,
+            reading,
+            lexeme_reading,
+        })
+    }
+}
+
+/// Get the kana reading of a token from its pronunciation and the reading
+/// of its lexeme. Pronunciations write long vowels with ー and particles as
+/// pronounced, e.g. コーコー for 高校, so the lexeme's reading is used with
+/// the token's conjugated ending. The pronunciation is kept when the lexeme
+/// is read differently, e.g. ワタクシ for 私 pronounced ワタシ.
+#[cfg(any(feature = "lindera-ipadic", feature = "lindera-unidic"))]
+fn kana_reading(
+    text: &str,
+    base: Option<&str>,
+    pronunciation: Option<String>,
+    lexeme: Option<String>,
+) -> Option<String> {
+    use wana_kana::ConvertJapanese;
+
+    let reading = lexeme.and_then(|lexeme| {
+        // Swap the ending of the base form for the ending as written, e.g.
+        // たべる to たべ for 食べ.
+        let base = base.unwrap_or(text);
+        let prefix = text
+            .chars()
+            .zip(base.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum::<usize>();
+        let stem = lexeme
+            .to_hiragana()
+            .strip_suffix(base[prefix..].to_hiragana().as_str())?
+            .to_string();
+        Some(stem + &text[prefix..].to_hiragana())
+    });
+    match (reading, pronunciation) {
+        (Some(reading), Some(pronunciation))
+            if reading.chars().count() == pronunciation.chars().count() =>
+        {
+            Some(reading)
+        }
+        (_, Some(pronunciation)) => Some(pronunciation.to_hiragana()),
+        (reading, None) => reading,
+    }
+}
+
+#[cfg(any(feature = "lindera-ipadic", feature = "lindera-unidic"))]
+impl Tokenizer for LinderaTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<Token> {
+        use wana_kana::ConvertJapanese;
+
+        let mut tokens = match self.tokenizer.tokenize(text) {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                tracing::warn!("Unable to tokenize text: {e}");
+                return vec![Token {
+                    text: text.to_string(),
+                    ..Default::default()
+                }];
+            }
+        };
+
+        tokens
+            .iter_mut()
+            .map(|token| {
+                let details: Vec<String> = token
+                    .details()
+                    .iter()
+                    .map(|detail| detail.to_string())
+                    .collect();
+                // Unknown words and missing fields are marked as such.
+                let detail = |index: usize| {
+                    details
+                        .get(index)
+                        .filter(|detail| !matches!(detail.as_str(), "*" | "UNK" | ""))
+                        .cloned()
+                };
+                let text = token.text.to_string();
+                let pos = detail(0);
+                let base = detail(self.base);
+                let reading = match self.lexeme_reading {
+                    Some(lexeme) => {
+                        kana_reading(&text, base.as_deref(), detail(self.reading), detail(lexeme))
+                    }
+                    None => detail(self.reading).map(|reading| reading.to_hiragana()),
+                };
+                Token {
+                    is_word: !text.trim().is_empty()
+                        && !matches!(pos.as_deref(), Some("記号" | "補助記号" | "空白")),
+                    base,
+                    reading,
+                    pos,
+                    text,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,50 @@
+//! jdpub annotates documents with Japanese readings and definitions.
+//!
+//! The [`Pipeline`] covers each step, from loading the configuration to
+//! exporting the annotated book. The `jdpub` binary is a thin command line
+//! interface around it.
+
+pub mod book;
+#[cfg(not(target_arch = "wasm32"))]
+mod cli;
+pub mod config;
+pub mod export;
+pub mod import;
+pub mod language;
+#[cfg(not(target_arch = "wasm32"))]
+mod logging;
+#[cfg(not(target_arch = "wasm32"))]
+mod lookup;
+mod pipeline;
+mod prelude;
+pub mod stats;
+mod util;
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(not(target_arch = "wasm32"))]
+mod web;
+
+pub(crate) use prelude::internal::*;
+pub use prelude::*;
+
+/// Run the command line interface.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn run() -> Result<()> {
+    // Parse the CLI.
+    let cli = Cli::new()?;
+    crate::logging::init(&cli)?;
+
+    // Parse configuration and build the database.
+    let pipeline = Pipeline::new(cli.config()?)?;
+
+    // Run subcommands.
+    if let Some(command) = &cli.command {
+        return match command {
+            Command::Serve(args) => web::serve(args, pipeline).await,
+            Command::Stats(args) => stats::run(args, pipeline).await,
+            Command::Lookup(args) => lookup::run(args, pipeline),
+        };
+    }
+
+    Ok(pipeline.run().await?)
+}
@@ -0,0 +1,59 @@
+//! Explain how the words of some text are looked up and annotated.
+
+use super::*;
+
+/// Print the lookup of each word of the text.
+pub(crate) fn run(args: &LookupArgs, pipeline: Pipeline) -> Result<()> {
+    let text = args.text.join(" ");
+    let reports = pipeline.dict().explain(&text, pipeline.config())?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = reports.iter().map(row).collect();
+    print!(
+        "{}",
+        table(
+            &[
+                "Word", "Lemma", "Source", "Reading", "Meaning", "Level", "Furigana", "Tooltip",
+            ],
+            &rows,
+        )
+    );
+    Ok(())
+}
+
+/// Format a report as a table row.
+fn row(report: &TokenReport) -> Vec<String> {
+    let missing = || "-".to_string();
+    let (source, reading, meaning, level) = match &report.lookup {
+        Some(lookup) => {
+            let source = match (lookup.source, &lookup.deinflection) {
+                (Some(source), Some(_)) => format!("{source}, deinflected"),
+                (Some(source), None) => source.to_string(),
+                (None, _) => missing(),
+            };
+            (
+                source,
+                lookup.kana.clone(),
+                lookup.meaning.clone(),
+                stats::level(lookup).1,
+            )
+        }
+        None => ("not found".to_string(), missing(), missing(), missing()),
+    };
+    vec![
+        report.surface.clone(),
+        report.lemma.clone().unwrap_or_else(missing),
+        source,
+        reading,
+        meaning,
+        level,
+        report.furigana.clone().unwrap_or_else(missing),
+        match report.tooltip {
+            Some(_) => "yes".to_string(),
+            None => "no".to_string(),
+        },
+    ]
+}
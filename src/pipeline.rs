@@ -0,0 +1,153 @@
+//! Annotation pipeline.
+
+use std::sync::Arc;
+
+use super::*;
+
+/// Pipeline from configuration to an exported book. Each step can be called
+/// separately, e.g., to inspect or modify the book between steps.
+///
+/// ```no_run
+/// # async fn example() -> Result<(), jdpub::Error> {
+/// let config = jdpub::Pipeline::load_config(["book.toml"], true)?;
+/// let pipeline = jdpub::Pipeline::new(config)?;
+/// let mut book = pipeline.import().await?;
+/// pipeline.annotate(&mut book)?;
+/// pipeline.export(&mut book)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Pipeline {
+    config: Config,
+    db: Arc<DictDb>,
+}
+
+impl Pipeline {
+    /// Create a pipeline, loading the dictionary.
+    pub fn new(config: Config) -> Result<Self, Error> {
+        let db = DictDb::new(&config).map_err(Error::Dictionary)?;
+        Ok(Self::with_dict(config, Arc::new(db)))
+    }
+
+    /// Create a pipeline that shares an already loaded dictionary.
+    pub fn with_dict(config: Config, db: Arc<DictDb>) -> Self {
+        Self { config, db }
+    }
+
+    /// Load configuration files, merged in order over the built-in
+    /// configurations if `builtin` is set.
+    pub fn load_config(
+        paths: impl IntoIterator<Item = impl AsRef<Path>>,
+        builtin: bool,
+    ) -> Result<Config, Error> {
+        let mut config = match builtin {
+            true => Config::from_builtin().map_err(Error::Config)?,
+            false => Config::default(),
+        };
+        for path in paths {
+            let parsed_config = Config::from_file(path).map_err(Error::Config)?;
+            config.merge(parsed_config).map_err(Error::Config)?;
+        }
+        Ok(config)
+    }
+
+    /// Get the configuration.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Get the configuration, e.g., to change chapters or the output file.
+    pub fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+
+    /// Get the dictionary.
+    pub fn dict(&self) -> &Arc<DictDb> {
+        &self.db
+    }
+
+    /// Import the configured chapters.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn import(&self) -> Result<Book, Error> {
+        import::import(&self.config).await.map_err(Error::Import)
+    }
+
+    /// Add readings and definitions to every chapter.
+    pub fn annotate(&self, book: &mut Book) -> Result<(), Error> {
+        for chapter in book.chapters.iter_mut() {
+            self.db
+                .annotate(chapter, &self.config)
+                .map_err(Error::Annotate)?;
+        }
+        Ok(())
+    }
+
+    /// Export the book to the configured output file.
+    pub fn export(&self, book: &mut Book) -> Result<(), Error> {
+        export::export(book, &self.config).map_err(Error::Export)
+    }
+
+    /// Export the book in memory. The output type is still chosen from the
+    /// configured output file name.
+    pub fn generate(&self, book: &mut Book) -> Result<Vec<u8>, Error> {
+        export::generate(book, &self.config).map_err(Error::Export)
+    }
+
+    /// Collect vocabulary statistics of the book, keeping the `top` most
+    /// frequent unknown words.
+    pub fn stats(&self, book: &Book, top: usize) -> Result<stats::BookStats, Error> {
+        stats::BookStats::new(book, &self.db, &self.config, top).map_err(Error::Stats)
+    }
+
+    /// Import, annotate and export the book.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn run(&self) -> Result<(), Error> {
+        let mut book = self.import().await?;
+        self.annotate(&mut book)?;
+        self.export(&mut book)
+    }
+}
+
+/// Pipeline error, by the step that failed.
+#[derive(Debug)]
+pub enum Error {
+    /// Configuration couldn't be loaded.
+    Config(anyhow::Error),
+    /// The dictionary couldn't be loaded.
+    Dictionary(anyhow::Error),
+    /// Chapters couldn't be imported.
+    Import(anyhow::Error),
+    /// Chapters couldn't be annotated.
+    Annotate(anyhow::Error),
+    /// The book couldn't be exported.
+    Export(anyhow::Error),
+    /// Statistics couldn't be collected.
+    Stats(anyhow::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The underlying error is available as the source.
+        match self {
+            Self::Config(_) => write!(f, "Failed to load configuration"),
+            Self::Dictionary(_) => write!(f, "Failed to load dictionary"),
+            Self::Import(_) => write!(f, "Failed to import"),
+            Self::Annotate(_) => write!(f, "Failed to annotate"),
+            Self::Export(_) => write!(f, "Failed to export"),
+            Self::Stats(_) => write!(f, "Failed to collect statistics"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Config(e)
+            | Self::Dictionary(e)
+            | Self::Import(e)
+            | Self::Annotate(e)
+            | Self::Export(e)
+            | Self::Stats(e) => Some(e.as_ref()),
+        }
+    }
+}
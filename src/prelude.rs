@@ -1,7 +1,9 @@
 pub use crate::book::*;
-pub use crate::cli::Cli;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use crate::cli::{Cli, Command, LookupArgs, StatsArgs};
 pub use crate::config::*;
 pub use crate::language::*;
+pub use crate::pipeline::{Error, Pipeline};
 
 pub mod internal {
     pub use std::borrow::Cow;
@@ -13,8 +15,10 @@ pub mod internal {
     pub use anyhow::{Result, anyhow, bail};
     pub use charabia::Tokenize;
 
-    pub use clap::Parser;
+    #[cfg(not(target_arch = "wasm32"))]
+    pub use clap::{Args, Parser, Subcommand};
     pub use rust_embed::RustEmbed;
+    pub use schemars::JsonSchema;
     pub use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
     pub use durf_parser as durf;
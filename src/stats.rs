@@ -0,0 +1,436 @@
+//! Vocabulary statistics, to compare how difficult books are.
+
+use std::collections::HashSet;
+
+use super::*;
+
+/// Highest frequency rank of each frequency band.
+const FREQUENCY_BANDS: &[u64] = &[1000, 2000, 5000, 10_000, 20_000, 50_000];
+
+/// Vocabulary statistics of a book, from the same lookups used to annotate
+/// it.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BookStats {
+    /// Book title.
+    pub title: String,
+    /// Vocabulary of the whole book.
+    pub total: VocabularyStats,
+    /// Vocabulary of each chapter.
+    pub chapters: Vec<ChapterStats>,
+    /// Most frequent words missing from the known words, or needing a
+    /// definition without a known words list.
+    pub top_unknown: Vec<WordStats>,
+}
+
+/// Vocabulary statistics of a chapter.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChapterStats {
+    /// Chapter title.
+    pub title: String,
+    /// Vocabulary of the chapter.
+    #[serde(flatten)]
+    pub stats: VocabularyStats,
+}
+
+/// Word counts of some text.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VocabularyStats {
+    /// Words, counting repeats.
+    pub words: usize,
+    /// Distinct words.
+    pub distinct: usize,
+    /// Words without a dictionary entry.
+    pub misses: usize,
+    /// Words that get a definition at the configured levels.
+    pub defined: usize,
+    /// Words missing from the known words, if a list is configured.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unknown: Option<usize>,
+    /// Words by level, easiest first.
+    pub levels: Vec<Count>,
+    /// Words by frequency rank, most frequent first.
+    pub frequency: Vec<Count>,
+}
+
+/// Words in a category, e.g. a level.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Count {
+    /// Category, e.g. N3 or 1-1000.
+    pub label: String,
+    /// Words, counting repeats.
+    pub words: usize,
+}
+
+/// A word and how often it appears.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WordStats {
+    /// Word as written.
+    pub word: String,
+    /// Times the word appears.
+    pub count: usize,
+    /// Reading of the word.
+    pub reading: String,
+    /// Meaning of the word.
+    pub meaning: String,
+    /// Level of the word.
+    pub level: String,
+}
+
+impl BookStats {
+    /// Collect the statistics of a book, keeping the `top` most frequent
+    /// unknown words.
+    pub fn new(book: &Book, db: &DictDb, config: &Config, top: usize) -> Result<Self> {
+        let known = load_known_words(&config.language.known_words)?;
+        let mut stats = Self {
+            title: book.title.clone(),
+            ..Default::default()
+        };
+        let mut total = Tally::default();
+        let mut words: HashMap<String, WordStats> = HashMap::new();
+        let mut order: Vec<String> = Vec::new();
+
+        for (i, chapter) in book.chapters.iter().enumerate() {
+            let language = chapter.language.unwrap_or(config.language.language());
+            let backend = db.backend(language)?;
+            let mut texts = Vec::new();
+            collect_texts(&chapter.ast.root, &mut texts);
+
+            let mut tally = Tally::default();
+            for text in texts.iter() {
+                let tokens =
+                    backend.merge_tokens(backend.tokenize(text), config.language.max_span());
+                for (i, token) in tokens.iter().enumerate() {
+                    // Latin text and numbers aren't in the book's language.
+                    if !token.is_word || token.text.is_ascii() {
+                        continue;
+                    }
+                    let previous = i.checked_sub(1).and_then(|i| tokens.get(i));
+                    let lookup = backend.lookup_token(token, previous, tokens.get(i + 1), config);
+                    let defined = lookup.as_ref().is_some_and(|lookup| {
+                        lookup.name.is_some()
+                            || backend
+                                .difficulty(&token.text, lookup, &config.language)
+                                .definition
+                    });
+                    let is_known = known.as_ref().map(|known| {
+                        known.contains(&token.text)
+                            || token.base.as_ref().is_some_and(|base| known.contains(base))
+                            || lookup
+                                .as_ref()
+                                .and_then(|lookup| lookup.deinflection.as_ref())
+                                .is_some_and(|deinflection| known.contains(&deinflection.base))
+                    });
+
+                    tally.add(token, lookup.as_ref(), defined, is_known);
+                    total.add(token, lookup.as_ref(), defined, is_known);
+
+                    if is_known.unwrap_or(!defined) {
+                        continue;
+                    }
+                    let word = words.entry(token.text.clone()).or_insert_with(|| {
+                        order.push(token.text.clone());
+                        WordStats {
+                            word: token.text.clone(),
+                            reading: lookup
+                                .as_ref()
+                                .map(|lookup| lookup.kana.clone())
+                                .unwrap_or_default(),
+                            meaning: lookup
+                                .as_ref()
+                                .map(|lookup| lookup.meaning.clone())
+                                .unwrap_or_default(),
+                            level: lookup.as_ref().map(level).unwrap_or_default().1,
+                            ..Default::default()
+                        }
+                    });
+                    word.count += 1;
+                }
+            }
+
+            stats.chapters.push(ChapterStats {
+                title: chapter
+                    .title
+                    .clone()
+                    .unwrap_or_else(|| format!("Chapter {}", i + 1)),
+                stats: tally.finish(),
+            });
+        }
+        stats.total = total.finish();
+
+        // Most frequent first, then in order of appearance.
+        let mut top_unknown: Vec<WordStats> = order
+            .into_iter()
+            .filter_map(|word| words.remove(&word))
+            .collect();
+        top_unknown.sort_by_key(|word| std::cmp::Reverse(word.count));
+        top_unknown.truncate(top);
+        stats.top_unknown = top_unknown;
+
+        Ok(stats)
+    }
+}
+
+impl std::fmt::Display for BookStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.title.is_empty() {
+            writeln!(f, "{}\n", self.title)?;
+        }
+
+        let total = &self.total;
+        let mut summary = vec![
+            vec!["Words".to_string(), total.words.to_string()],
+            vec!["Distinct words".to_string(), total.distinct.to_string()],
+            vec![
+                "Defined".to_string(),
+                format!(
+                    "{} ({})",
+                    total.defined,
+                    percent(total.defined, total.words)
+                ),
+            ],
+            vec![
+                "Lookup misses".to_string(),
+                format!("{} ({})", total.misses, percent(total.misses, total.words)),
+            ],
+        ];
+        if let Some(unknown) = total.unknown {
+            summary.push(vec![
+                "Unknown".to_string(),
+                format!("{unknown} ({})", percent(unknown, total.words)),
+            ]);
+        }
+        writeln!(f, "{}", table(&[], &summary))?;
+
+        for (heading, counts) in [("Level", &total.levels), ("Frequency", &total.frequency)] {
+            let rows: Vec<Vec<String>> = counts
+                .iter()
+                .map(|count| {
+                    vec![
+                        count.label.clone(),
+                        count.words.to_string(),
+                        percent(count.words, total.words),
+                    ]
+                })
+                .collect();
+            writeln!(f, "{}", table(&[heading, "Words", "Share"], &rows))?;
+        }
+
+        let rows: Vec<Vec<String>> = self
+            .chapters
+            .iter()
+            .map(|chapter| {
+                let stats = &chapter.stats;
+                vec![
+                    chapter.title.clone(),
+                    stats.words.to_string(),
+                    stats.distinct.to_string(),
+                    percent(stats.defined, stats.words),
+                    stats
+                        .unknown
+                        .map(|unknown| percent(unknown, stats.words))
+                        .unwrap_or_else(|| "-".to_string()),
+                    percent(stats.misses, stats.words),
+                ]
+            })
+            .collect();
+        writeln!(
+            f,
+            "{}",
+            table(
+                &[
+                    "Chapter", "Words", "Distinct", "Defined", "Unknown", "Misses"
+                ],
+                &rows
+            )
+        )?;
+
+        let rows: Vec<Vec<String>> = self
+            .top_unknown
+            .iter()
+            .map(|word| {
+                vec![
+                    word.word.clone(),
+                    word.count.to_string(),
+                    word.reading.clone(),
+                    word.level.clone(),
+                    word.meaning.clone(),
+                ]
+            })
+            .collect();
+        write!(
+            f,
+            "{}",
+            table(
+                &["Unknown word", "Count", "Reading", "Level", "Meaning"],
+                &rows
+            )
+        )
+    }
+}
+
+/// Running word counts.
+#[derive(Default)]
+struct Tally {
+    words: usize,
+    distinct: HashSet<String>,
+    misses: usize,
+    defined: usize,
+    unknown: Option<usize>,
+    /// Words by level, keyed by difficulty then label.
+    levels: HashMap<(i32, String), usize>,
+    /// Words by frequency band, then words ranked below every band and
+    /// unranked words.
+    frequency: Vec<usize>,
+}
+
+impl Tally {
+    fn add(
+        &mut self,
+        token: &Token,
+        lookup: Option<&DictLookup>,
+        defined: bool,
+        known: Option<bool>,
+    ) {
+        self.words += 1;
+        self.distinct.insert(token.text.clone());
+        if defined {
+            self.defined += 1;
+        }
+        if let Some(known) = known {
+            *self.unknown.get_or_insert(0) += usize::from(!known);
+        }
+        let Some(lookup) = lookup else {
+            self.misses += 1;
+            return;
+        };
+
+        *self.levels.entry(level(lookup)).or_default() += 1;
+        self.frequency.resize(FREQUENCY_BANDS.len() + 2, 0);
+        let band = match lookup.frequency {
+            Some(rank) => FREQUENCY_BANDS
+                .iter()
+                .position(|band| rank <= *band)
+                .unwrap_or(FREQUENCY_BANDS.len()),
+            None => FREQUENCY_BANDS.len() + 1,
+        };
+        self.frequency[band] += 1;
+    }
+
+    fn finish(self) -> VocabularyStats {
+        let mut levels: Vec<((i32, String), usize)> = self.levels.into_iter().collect();
+        levels.sort();
+        VocabularyStats {
+            words: self.words,
+            distinct: self.distinct.len(),
+            misses: self.misses,
+            defined: self.defined,
+            unknown: self.unknown,
+            levels: levels
+                .into_iter()
+                .map(|((_, label), words)| Count { label, words })
+                .collect(),
+            frequency: self
+                .frequency
+                .iter()
+                .enumerate()
+                .filter(|(_, words)| **words > 0)
+                .map(|(i, words)| Count {
+                    label: frequency_band(i),
+                    words: *words,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Get the level of a lookup with a key to sort levels from easiest to
+/// hardest.
+pub(crate) fn level(lookup: &DictLookup) -> (i32, String) {
+    match (&lookup.name, lookup.hsk) {
+        (Some(_), _) => (i32::MAX, "Name".to_string()),
+        (None, Some(hsk)) => (hsk as i32, hsk.to_string()),
+        // Lower JLPT levels are harder, and words without a level hardest.
+        (None, None) => (-(lookup.jlpt as i32), lookup.jlpt.to_string()),
+    }
+}
+
+/// Label a frequency band by its ranks.
+fn frequency_band(band: usize) -> String {
+    match (band.checked_sub(1), FREQUENCY_BANDS.get(band)) {
+        (None, Some(high)) => format!("1-{high}"),
+        (Some(low), Some(high)) => format!("{}-{high}", FREQUENCY_BANDS[low] + 1),
+        (Some(low), None) if band == FREQUENCY_BANDS.len() => {
+            format!("Over {}", FREQUENCY_BANDS[low])
+        }
+        _ => "Unranked".to_string(),
+    }
+}
+
+/// Load word lists, with a word per line. Only the first column of csv or
+/// tsv lines is used, and lines starting with # are skipped.
+fn load_known_words(paths: &[PathBuf]) -> Result<Option<HashSet<String>>> {
+    if paths.is_empty() {
+        return Ok(None);
+    }
+    let mut known = HashSet::new();
+    for path in paths.iter() {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => bail!(
+                "Unable to read known words '{}': {e}",
+                path.to_string_lossy()
+            ),
+        };
+        for line in content.lines() {
+            let word = line.split(['\t', ',']).next().unwrap_or_default().trim();
+            if !word.is_empty() && !word.starts_with('#') {
+                known.insert(word.to_string());
+            }
+        }
+    }
+
+    tracing::debug!("Loaded {} known words.", known.len());
+
+    Ok(Some(known))
+}
+
+/// Collect the text of each text node, as it's annotated.
+fn collect_texts(node: &durf::Node, texts: &mut Vec<String>) {
+    match &**node {
+        durf_parser::RawNode::Empty => {}
+        durf_parser::RawNode::Section(section) => {
+            for node in section.nodes.iter() {
+                collect_texts(node, texts);
+            }
+        }
+        durf_parser::RawNode::Text(text) => {
+            let text = text
+                .fragments
+                .iter()
+                .fold(String::new(), |acc, el| acc + &el.text)
+                .replace("\n", " ")
+                .replace("　", "");
+            texts.push(text);
+        }
+    }
+}
+
+/// Format a share as a percentage.
+fn percent(count: usize, total: usize) -> String {
+    match total {
+        0 => "-".to_string(),
+        _ => format!("{:.1}%", count as f64 * 100.0 / total as f64),
+    }
+}
+
+/// Print the statistics of the inputs.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn run(args: &StatsArgs, pipeline: Pipeline) -> Result<()> {
+    let book = pipeline.import().await?;
+    let stats = pipeline.stats(&book, args.top)?;
+    match args.json {
+        true => println!("{}", serde_json::to_string_pretty(&stats)?),
+        false => print!("{stats}"),
+    }
+    Ok(())
+}
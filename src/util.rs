@@ -47,6 +47,31 @@ pub fn read_embedded_toml<T: DeserializeOwned, Embed: RustEmbed>(
     }
 }
 
+/// Get the heading level of a text node, if every fragment is part of the same
+/// heading.
+pub fn text_heading(text: &durf::Text) -> Option<u8> {
+    let mut level = None;
+    for fragment in text.fragments.iter() {
+        match (fragment.attributes.heading, level) {
+            (None, _) => return None,
+            (Some(heading), None) => level = Some(heading),
+            (Some(heading), Some(current)) if heading != current => return None,
+            _ => {}
+        }
+    }
+    level
+}
+
+/// Get the text of a text node as it is tokenized for annotation.
+pub fn annotated_text(text: &durf::Text) -> String {
+    // TODO: Configurable skip characters.
+    text.fragments
+        .iter()
+        .fold(String::new(), |acc, el| acc + &el.text)
+        .replace("\n", " ")
+        .replace("　", "")
+}
+
 pub fn get_mimetype(resource: impl AsRef<str>) -> &'static str {
     let resource = resource.as_ref();
 
@@ -91,3 +116,62 @@ pub fn get_mimetype(resource: impl AsRef<str>) -> &'static str {
         "application/octet-stream"
     }
 }
+
+/// Format rows as a table, with aligned columns.
+pub fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let headers: Vec<String> = headers.iter().map(|header| header.to_string()).collect();
+    let lines: Vec<&Vec<String>> = match headers.is_empty() {
+        true => rows.iter().collect(),
+        false => std::iter::once(&headers).chain(rows.iter()).collect(),
+    };
+    let columns = lines
+        .iter()
+        .map(|line| line.len())
+        .max()
+        .unwrap_or_default();
+    let widths: Vec<usize> = (0..columns)
+        .map(|column| {
+            lines
+                .iter()
+                .filter_map(|line| line.get(column))
+                .map(|cell| width(cell))
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let mut out = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        let cells: Vec<String> = line
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, column)| cell.clone() + &" ".repeat(column - width(cell)))
+            .collect();
+        out += cells.join("  ").trim_end();
+        out += "\n";
+        if i == 0 && !headers.is_empty() {
+            let rule: Vec<String> = widths.iter().map(|column| "-".repeat(*column)).collect();
+            out += &rule.join("  ");
+            out += "\n";
+        }
+    }
+    out
+}
+
+/// Get the width of text in a terminal, where CJK characters are two
+/// columns wide.
+pub fn width(text: &str) -> usize {
+    text.chars()
+        .map(|c| match c {
+            '\u{1100}'..='\u{115F}'
+            | '\u{2E80}'..='\u{A4CF}'
+            | '\u{AC00}'..='\u{D7A3}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{FE30}'..='\u{FE4F}'
+            | '\u{FF00}'..='\u{FF60}'
+            | '\u{FFE0}'..='\u{FFE6}'
+            | '\u{20000}'..='\u{3FFFD}' => 2,
+            _ => 1,
+        })
+        .sum()
+}
@@ -0,0 +1,83 @@
+//! WebAssembly bindings for converting books in the browser.
+
+use wasm_bindgen::prelude::*;
+
+use super::*;
+
+/// Book converter. Chapters are added as bytes and the exported book is
+/// returned as bytes, so no server or filesystem is needed.
+#[wasm_bindgen]
+pub struct Converter {
+    config: Config,
+    db: DictDb,
+    book: Book,
+}
+
+#[wasm_bindgen]
+impl Converter {
+    /// Create a converter from a toml configuration, merged over the built-in
+    /// configurations, and an optional jmdict-simplified json dictionary.
+    #[wasm_bindgen(constructor)]
+    pub fn new(config: &str, dictionary: Option<Vec<u8>>) -> Result<Converter, JsError> {
+        let mut builtin = Config::from_builtin().map_err(js_error)?;
+        let parsed_config: Config = match toml::from_str(config) {
+            Ok(c) => c,
+            Err(e) => return Err(JsError::new(&format!("Failed to parse config: {e}"))),
+        };
+        builtin.merge(parsed_config).map_err(js_error)?;
+
+        let mut japanese = JapaneseBackend::new(&builtin).map_err(js_error)?;
+        if let Some(dictionary) = dictionary {
+            japanese.load_jmdict_json(&dictionary).map_err(js_error)?;
+        }
+        let db = DictDb::with_japanese(&builtin, japanese).map_err(js_error)?;
+        let book = builtin.export.book().map_err(js_error)?;
+
+        Ok(Self {
+            config: builtin,
+            db,
+            book,
+        })
+    }
+
+    /// Add a chapter from an html, text or epub file.
+    #[wasm_bindgen(js_name = addChapter)]
+    pub fn add_chapter(&mut self, name: &str, data: &[u8]) -> Result<(), JsError> {
+        if name.ends_with(".epub") {
+            return import::import_epub_bytes(data, &mut self.book).map_err(js_error);
+        }
+
+        let title = match name.rsplit_once('.') {
+            Some((stem, _)) if !stem.is_empty() => Some(stem.to_string()),
+            _ => None,
+        };
+        let body = String::from_utf8_lossy(data);
+        let chapter = import::parse_html(&body, title, &self.config).map_err(js_error)?;
+        self.book.chapters.push(chapter);
+        Ok(())
+    }
+
+    /// Annotate the added chapters and export them. The output type is chosen
+    /// from the output file name, like the `--output` argument. The added
+    /// chapters are cleared afterwards.
+    pub fn export(&mut self, output: &str) -> Result<Vec<u8>, JsError> {
+        let mut book = self.config.export.book().map_err(js_error)?;
+        std::mem::swap(&mut book, &mut self.book);
+        if book.chapters.is_empty() {
+            return Err(JsError::new("No chapters were added."));
+        }
+
+        let mut config = self.config.clone();
+        config.export.output_file = PathBuf::from(output);
+        for chapter in book.chapters.iter_mut() {
+            self.db.annotate(chapter, &config).map_err(js_error)?;
+        }
+
+        export::generate(&mut book, &config).map_err(js_error)
+    }
+}
+
+/// Convert an error for javascript.
+fn js_error(e: anyhow::Error) -> JsError {
+    JsError::new(&e.to_string())
+}
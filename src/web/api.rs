@@ -0,0 +1,409 @@
+use serde_json::json;
+
+use super::*;
+
+/// Routes served by the API.
+const ROUTES: &[&str] = &["/annotate", "/lookup", "/books", "/openapi.json"];
+
+/// Check if a path is an API route.
+pub fn is_route(path: &str) -> bool {
+    ROUTES.contains(&path)
+}
+
+/// Handle an API request.
+pub async fn handle(server: &Server, req: Request<Incoming>) -> Response<Full<Bytes>> {
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let origin = req
+        .headers()
+        .get(hyper::header::ORIGIN)
+        .filter(|origin| {
+            server
+                .allow_origins
+                .iter()
+                .any(|allowed| origin.as_bytes() == allowed.as_bytes())
+        })
+        .cloned();
+
+    let res = match (&method, path.as_str()) {
+        // Allow the configured origins to call the API.
+        (&Method::OPTIONS, _) => Ok(response(StatusCode::NO_CONTENT, "text/plain", "")),
+        (&Method::POST, "/annotate") => annotate(server, req).await,
+        (&Method::GET, "/lookup") => lookup(server, &req),
+        (&Method::POST, "/books") => books(server, req).await,
+        (&Method::GET, "/openapi.json") => Ok(json_response(StatusCode::OK, &openapi())),
+        _ => Err(ApiError::new(
+            StatusCode::METHOD_NOT_ALLOWED,
+            format!("{method} is not supported for {path}"),
+        )),
+    };
+
+    let mut res = match res {
+        Ok(res) => res,
+        Err(e) => {
+            tracing::error!("{method} {path} failed: {}", e.error);
+            json_response(e.status, &e)
+        }
+    };
+    let headers = res.headers_mut();
+    headers.insert(
+        hyper::header::VARY,
+        hyper::header::HeaderValue::from_static("Origin"),
+    );
+    let Some(origin) = origin else {
+        return res;
+    };
+    headers.insert(hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+    headers.insert(
+        hyper::header::ACCESS_CONTROL_ALLOW_METHODS,
+        hyper::header::HeaderValue::from_static("GET, POST, OPTIONS"),
+    );
+    headers.insert(
+        hyper::header::ACCESS_CONTROL_ALLOW_HEADERS,
+        hyper::header::HeaderValue::from_static("Content-Type"),
+    );
+    res
+}
+
+/// Annotate text or html.
+async fn annotate(server: &Server, req: Request<Incoming>) -> ApiResult {
+    let body = read_body(req).await?;
+    let request: AnnotateRequest = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => return Err(ApiError::bad_request(format!("Invalid request: {e}"))),
+    };
+
+    let mut config = server.config.clone();
+    if let Some(language) = request.language {
+        config.language.merge_request(language)?;
+    }
+
+    let html = match (request.html, request.text) {
+        (Some(html), _) => html,
+        (None, Some(text)) => text_to_html(&text),
+        (None, None) => return Err(ApiError::bad_request("Either text or html is required.")),
+    };
+    let db = server.db.clone();
+    let format = request.format;
+    let annotated = blocking(move || annotate_html(&db, &config, &html, format)).await?;
+
+    Ok(json_response(StatusCode::OK, &annotated))
+}
+
+/// Annotate html in the requested format.
+fn annotate_html(
+    db: &DictDb,
+    config: &Config,
+    html: &str,
+    format: AnnotateFormat,
+) -> Result<AnnotateResponse> {
+    let mut ast = match durf::Ast::from_html(html, config.parse.html.parse_flags()?) {
+        Ok(ast) => ast,
+        Err(e) => bail!("Failed to parse html: {e}"),
+    };
+    ast.minimize();
+
+    Ok(match format {
+        AnnotateFormat::Html => {
+            db.transform(&mut ast.root, config)?;
+            AnnotateResponse {
+                html: Some(crate::export::render_html(ast)?),
+                paragraphs: None,
+            }
+        }
+        AnnotateFormat::Tokens => {
+            let mut paragraphs = Vec::new();
+            collect_paragraphs(db, config, &ast.root, &mut paragraphs)?;
+            AnnotateResponse {
+                html: None,
+                paragraphs: Some(paragraphs),
+            }
+        }
+    })
+}
+
+/// Collect the tokens of each text node, with the lookups used to annotate
+/// them.
+fn collect_paragraphs(
+    db: &DictDb,
+    config: &Config,
+    node: &durf::Node,
+    paragraphs: &mut Vec<Vec<Token>>,
+) -> Result<()> {
+    match &**node {
+        durf_parser::RawNode::Empty => {}
+        durf_parser::RawNode::Section(section) => {
+            for node in section.nodes.iter() {
+                collect_paragraphs(db, config, node, paragraphs)?;
+            }
+        }
+        durf_parser::RawNode::Text(text) => {
+            let tokens: Vec<Token> = db
+                .annotate_text(&annotated_text(text), config)?
+                .into_iter()
+                .map(|token| {
+                    let attributes = token.attributes.unwrap_or_default();
+                    Token {
+                        text: token.token.text,
+                        reading: attributes.annotation,
+                        definition: match attributes.tooltip {
+                            Some(_) => token.lookup,
+                            None => None,
+                        },
+                    }
+                })
+                .collect();
+            if !tokens.is_empty() {
+                paragraphs.push(tokens);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Look up a single word.
+fn lookup(server: &Server, req: &Request<Incoming>) -> ApiResult {
+    let query = req.uri().query().unwrap_or_default();
+    let word = match form_urlencoded::parse(query.as_bytes()).find(|(key, _)| key == "word") {
+        Some((_, word)) => word.to_string(),
+        None => return Err(ApiError::bad_request("The word parameter is required.")),
+    };
+
+    match server.db.lookup(&word, server.config.language.language()) {
+        Some(lookup) => Ok(json_response(StatusCode::OK, &lookup)),
+        None => Err(ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("No definition found for {word}."),
+        )),
+    }
+}
+
+/// Build an epub from a toml config.
+async fn books(server: &Server, req: Request<Incoming>) -> ApiResult {
+    let body = read_body(req).await?;
+    let config_text = match std::str::from_utf8(&body) {
+        Ok(text) => text,
+        Err(e) => return Err(ApiError::bad_request(format!("Config is not utf-8: {e}"))),
+    };
+    let mut parsed_config: Config = match toml::from_str(config_text) {
+        Ok(c) => c,
+        Err(e) => {
+            return Err(ApiError::bad_request(format!(
+                "Failed to parse config: {e}"
+            )));
+        }
+    };
+
+    check_books_config(&parsed_config)?;
+
+    let mut config = server.config.clone();
+    config.import.chapters.clear();
+    let language = std::mem::take(&mut parsed_config.language);
+    config.merge(parsed_config)?;
+    config.language.merge_request(language)?;
+    if config.import.chapters.is_empty() {
+        return Err(ApiError::bad_request("The config has no chapters."));
+    }
+
+    let data = server.build(config, "book.epub").await?;
+    Ok(epub_response(data))
+}
+
+/// Reject settings of a book request that could read local files or change how
+/// the server fetches pages.
+fn check_books_config(config: &Config) -> Result<()> {
+    if !config.parse.html.is_default() {
+        bail!("Parse rules can only be set in the server's configuration.");
+    }
+    if config.export.cover.is_some() {
+        bail!("A cover can only be set in the server's configuration.");
+    }
+    for chapter in config.import.chapters.iter() {
+        if !chapter.uri.starts_with("http://") && !chapter.uri.starts_with("https://") {
+            bail!(
+                "Only http and https chapters are supported: {}",
+                chapter.uri
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Build the OpenAPI description of the API.
+fn openapi() -> serde_json::Value {
+    let mut generator = schemars::generate::SchemaSettings::openapi3().into_generator();
+    let annotate_request = generator.subschema_for::<AnnotateRequest>();
+    let annotate_response = generator.subschema_for::<AnnotateResponse>();
+    let lookup_response = generator.subschema_for::<DictLookup>();
+    let error = generator.subschema_for::<ApiError>();
+    let error_response = json!({
+        "description": "Error",
+        "content": { "application/json": { "schema": error } },
+    });
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "jdpub",
+            "description": env!("CARGO_PKG_DESCRIPTION"),
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": {
+            "/annotate": {
+                "post": {
+                    "summary": "Annotate text or html with readings and definitions.",
+                    "requestBody": {
+                        "required": true,
+                        "content": { "application/json": { "schema": annotate_request } },
+                    },
+                    "responses": {
+                        "200": {
+                            "description": "Annotated html or tokens, depending on the requested format.",
+                            "content": { "application/json": { "schema": annotate_response } },
+                        },
+                        "400": error_response,
+                    },
+                },
+            },
+            "/lookup": {
+                "get": {
+                    "summary": "Look up a word in the dictionary.",
+                    "parameters": [{
+                        "name": "word",
+                        "in": "query",
+                        "required": true,
+                        "schema": { "type": "string" },
+                    }],
+                    "responses": {
+                        "200": {
+                            "description": "Dictionary entry.",
+                            "content": { "application/json": { "schema": lookup_response } },
+                        },
+                        "400": error_response,
+                        "404": error_response,
+                    },
+                },
+            },
+            "/books": {
+                "post": {
+                    "summary": "Build an epub from a configuration file with http or https chapters.",
+                    "requestBody": {
+                        "required": true,
+                        "content": { "application/toml": { "schema": { "type": "string" } } },
+                    },
+                    "responses": {
+                        "200": {
+                            "description": "Generated epub.",
+                            "content": {
+                                "application/epub+zip": {
+                                    "schema": { "type": "string", "format": "binary" },
+                                },
+                            },
+                        },
+                        "400": error_response,
+                    },
+                },
+            },
+        },
+        "components": { "schemas": generator.take_definitions(true) },
+    })
+}
+
+/// Build a json response.
+fn json_response(status: StatusCode, value: &impl Serialize) -> Response<Full<Bytes>> {
+    match serde_json::to_vec(value) {
+        Ok(body) => response(status, "application/json", body),
+        Err(e) => response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "text/plain; charset=utf-8",
+            e.to_string(),
+        ),
+    }
+}
+
+type ApiResult = std::result::Result<Response<Full<Bytes>>, ApiError>;
+
+/// Error returned by the API.
+#[derive(Debug, Serialize, JsonSchema)]
+struct ApiError {
+    /// Description of the error.
+    error: String,
+    #[serde(skip)]
+    status: StatusCode,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, error: impl Into<String>) -> Self {
+        Self {
+            error: error.into(),
+            status,
+        }
+    }
+
+    fn bad_request(error: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, error)
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(value: anyhow::Error) -> Self {
+        Self::bad_request(format!("{value:#}"))
+    }
+}
+
+/// Annotation request.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct AnnotateRequest {
+    /// Plain text to annotate, one paragraph per line.
+    #[serde(default)]
+    text: Option<String>,
+    /// Html to annotate, used instead of the text if both are given.
+    #[serde(default)]
+    html: Option<String>,
+    /// Response format.
+    #[serde(default)]
+    format: AnnotateFormat,
+    /// Language and annotation thresholds, overriding the configured ones.
+    /// Dictionaries and other settings loaded when the server starts are
+    /// rejected.
+    #[serde(default)]
+    language: Option<LanguageConfig>,
+}
+
+/// Annotation response format.
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+enum AnnotateFormat {
+    /// Annotated html, as produced by the html export.
+    #[default]
+    Html,
+    /// Tokens of each paragraph.
+    Tokens,
+}
+
+/// Annotation response.
+#[derive(Debug, Serialize, JsonSchema)]
+struct AnnotateResponse {
+    /// Annotated html, for the html format.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    html: Option<String>,
+    /// Tokens of each paragraph, for the tokens format.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    paragraphs: Option<Vec<Vec<Token>>>,
+}
+
+/// Annotated token.
+#[derive(Debug, Serialize, JsonSchema)]
+struct Token {
+    /// Token text.
+    text: String,
+    /// Reading, if furigana is shown for the token.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reading: Option<String>,
+    /// Dictionary entry, if a definition is shown for the token.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    definition: Option<DictLookup>,
+}
@@ -0,0 +1,369 @@
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use base64::Engine;
+use http_body_util::{BodyExt, Full, Limited};
+use hyper::body::{Bytes, Incoming};
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+
+use super::*;
+use crate::cli::ServeArgs;
+
+mod api;
+
+/// Largest accepted request body. Uploads are base64 encoded, so this leaves
+/// room for a reasonably large epub.
+const MAX_REQUEST_SIZE: usize = 256 * 1024 * 1024;
+
+#[derive(RustEmbed)]
+#[folder = "metadata/web"]
+struct WebMetadata;
+
+/// Start the local web interface.
+pub async fn serve(args: &ServeArgs, pipeline: Pipeline) -> Result<()> {
+    let listener = match tokio::net::TcpListener::bind((args.host.as_str(), args.port)).await {
+        Ok(listener) => listener,
+        Err(e) => bail!("Unable to listen on {}:{}: {e}", args.host, args.port),
+    };
+    tracing::info!("Serving jdpub on http://{}/", listener.local_addr()?);
+    if args.api {
+        tracing::info!(
+            "Serving the JSON API, described at http://{}/openapi.json",
+            listener.local_addr()?
+        );
+    }
+
+    let server = Arc::new(Server {
+        config: pipeline.config().clone(),
+        db: pipeline.dict().clone(),
+        api: args.api,
+        allow_origins: args.allow_origins.clone(),
+    });
+
+    loop {
+        let (stream, _) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                tracing::warn!("Failed to accept connection: {e}");
+                continue;
+            }
+        };
+        let server = server.clone();
+        tokio::spawn(async move {
+            let service = hyper::service::service_fn(move |req| {
+                let server = server.clone();
+                async move { server.handle(req).await }
+            });
+            if let Err(e) = hyper::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                tracing::debug!("Connection error: {e}");
+            }
+        });
+    }
+}
+
+/// Shared server state.
+struct Server {
+    /// Configuration from the command line, used as the base for every book.
+    config: Config,
+    db: Arc<DictDb>,
+    /// Whether the JSON API is enabled.
+    api: bool,
+    /// Origins allowed to call the JSON API from a browser.
+    allow_origins: Vec<String>,
+}
+
+impl Server {
+    /// Route a request.
+    async fn handle(&self, req: Request<Incoming>) -> Result<Response<Full<Bytes>>, Infallible> {
+        let method = req.method().clone();
+        let path = req.uri().path().to_string();
+        tracing::debug!("{method} {path}");
+
+        if self.api && api::is_route(&path) {
+            return Ok(api::handle(self, req).await);
+        }
+
+        let res = match (&method, path.as_str()) {
+            (&Method::POST, "/preview") => self.convert(req, "preview.html").await,
+            (&Method::POST, "/epub") => self.convert(req, "book.epub").await,
+            (&Method::GET, "/") => asset("index.html"),
+            (&Method::GET, _) => asset(path.trim_start_matches('/')),
+            _ => Ok(response(StatusCode::METHOD_NOT_ALLOWED, "text/plain", "")),
+        };
+
+        Ok(match res {
+            Ok(res) => res,
+            Err(e) => {
+                tracing::error!("{method} {path} failed: {e:#}");
+                response(
+                    StatusCode::BAD_REQUEST,
+                    "text/plain; charset=utf-8",
+                    format!("{e:#}"),
+                )
+            }
+        })
+    }
+
+    /// Convert the request's inputs to a book, exported as the given file.
+    async fn convert(
+        &self,
+        req: Request<Incoming>,
+        file_name: &str,
+    ) -> Result<Response<Full<Bytes>>> {
+        let body = read_body(req).await?;
+        let request: ConvertRequest = match serde_json::from_slice(&body) {
+            Ok(request) => request,
+            Err(e) => bail!("Invalid request: {e}"),
+        };
+
+        let job = self.job()?;
+        let mut config = self.config.clone();
+        config.import.chapters = request.chapters(job.path())?;
+        if !request.title.is_empty() {
+            config.export.title = request.title;
+        }
+        if !request.author.is_empty() {
+            config.export.author = request.author;
+        }
+        if let Some(language) = request.language {
+            config.language.merge_request(language)?;
+        }
+
+        let data = self.build(config, file_name).await?;
+        Ok(match file_name.ends_with(".epub") {
+            true => epub_response(data),
+            false => response(StatusCode::OK, "text/html; charset=utf-8", data),
+        })
+    }
+
+    /// Create a new, private directory for a job, removed when dropped. The
+    /// directory is always newly created, so other users can't prepare it.
+    fn job(&self) -> Result<tempfile::TempDir> {
+        match tempfile::Builder::new().prefix("jdpub-").tempdir() {
+            Ok(dir) => Ok(dir),
+            Err(e) => bail!("Failed to create a job directory: {e}"),
+        }
+    }
+
+    /// Build a book in memory, exported according to the file name.
+    async fn build(&self, mut config: Config, file_name: &str) -> Result<Vec<u8>> {
+        config.export.output_file = PathBuf::from(file_name);
+
+        let pipeline = Pipeline::with_dict(config, self.db.clone());
+        blocking(move || {
+            let mut book = tokio::runtime::Handle::current().block_on(pipeline.import())?;
+            pipeline.annotate(&mut book)?;
+            Ok(pipeline.generate(&mut book)?)
+        })
+        .await
+    }
+}
+
+/// Run work that parses or annotates documents on the blocking thread pool,
+/// so that it doesn't hold up other connections.
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T> + Send + 'static,
+) -> Result<T> {
+    match tokio::task::spawn_blocking(work).await {
+        Ok(result) => result,
+        Err(e) => bail!("Conversion task failed: {e}"),
+    }
+}
+
+/// Book conversion request from the web interface.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConvertRequest {
+    /// Book title.
+    #[serde(default)]
+    title: String,
+    /// Book author.
+    #[serde(default)]
+    author: String,
+    /// Pasted text, used as the first chapter.
+    #[serde(default)]
+    text: String,
+    /// Uploaded files.
+    #[serde(default)]
+    files: Vec<UploadedFile>,
+    /// Websites to fetch.
+    #[serde(default)]
+    urls: Vec<String>,
+    /// Language and annotation thresholds, overriding the configured ones.
+    #[serde(default)]
+    language: Option<LanguageConfig>,
+}
+
+/// File uploaded from the web interface.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UploadedFile {
+    /// Original file name.
+    name: String,
+    /// Base64 encoded contents.
+    data: String,
+}
+
+impl ConvertRequest {
+    /// Write the inputs to the job directory and get their chapter configs.
+    fn chapters(&self, dir: &Path) -> Result<Vec<ChapterConfig>> {
+        let mut chapters = Vec::new();
+
+        if !self.text.trim().is_empty() {
+            let path = dir.join("text.html");
+            write_input(&path, text_to_html(&self.text).as_bytes())?;
+            chapters.push(ChapterConfig {
+                uri: path.to_string_lossy().to_string(),
+                ..Default::default()
+            });
+        }
+
+        for (i, file) in self.files.iter().enumerate() {
+            let data = match base64::engine::general_purpose::STANDARD.decode(&file.data) {
+                Ok(data) => data,
+                Err(e) => bail!("Invalid upload {}: {e}", file.name),
+            };
+            // Only keep the file name so uploads stay inside the job directory.
+            let name = match Path::new(&file.name).file_name() {
+                Some(name) => name.to_string_lossy().to_string(),
+                None => bail!("Invalid upload name: {}", file.name),
+            };
+            let stem = match Path::new(&name).file_stem() {
+                Some(stem) => stem.to_string_lossy().to_string(),
+                None => String::new(),
+            };
+
+            let title = match name.ends_with(".epub") {
+                true => String::new(),
+                false => stem.clone(),
+            };
+            let path = match name.ends_with(".txt") {
+                // Plain text is converted so its paragraphs survive parsing.
+                true => {
+                    let path = dir.join(format!("{i:03}-{stem}.html"));
+                    write_input(
+                        &path,
+                        text_to_html(&String::from_utf8_lossy(&data)).as_bytes(),
+                    )?;
+                    path
+                }
+                false => {
+                    let path = dir.join(format!("{i:03}-{name}"));
+                    write_input(&path, &data)?;
+                    path
+                }
+            };
+            chapters.push(ChapterConfig {
+                uri: path.to_string_lossy().to_string(),
+                title,
+                ..Default::default()
+            });
+        }
+
+        for url in self.urls.iter() {
+            let url = url.trim();
+            if url.is_empty() {
+                continue;
+            }
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                bail!("Only http and https urls are supported: {url}");
+            }
+            chapters.push(ChapterConfig {
+                uri: url.to_string(),
+                ..Default::default()
+            });
+        }
+
+        if chapters.is_empty() {
+            bail!("Nothing to convert. Paste text, upload a file or enter a url.");
+        }
+
+        Ok(chapters)
+    }
+}
+
+/// Read a request body.
+async fn read_body(req: Request<Incoming>) -> Result<Bytes> {
+    match Limited::new(req.into_body(), MAX_REQUEST_SIZE)
+        .collect()
+        .await
+    {
+        Ok(body) => Ok(body.to_bytes()),
+        Err(e) => bail!("Failed to read request: {e}"),
+    }
+}
+
+/// Write an uploaded input.
+fn write_input(path: &Path, data: &[u8]) -> Result<()> {
+    match std::fs::write(path, data) {
+        Ok(()) => Ok(()),
+        Err(e) => bail!("Failed to write {}: {e}", path.to_string_lossy()),
+    }
+}
+
+/// Wrap plain text in html, one paragraph per line.
+fn text_to_html(text: &str) -> String {
+    let mut html = String::from("<html><body>");
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        html += "<p>";
+        for c in line.chars() {
+            match c {
+                '&' => html += "&amp;",
+                '<' => html += "&lt;",
+                '>' => html += "&gt;",
+                '"' => html += "&quot;",
+                _ => html.push(c),
+            }
+        }
+        html += "</p>";
+    }
+    html += "</body></html>";
+    html
+}
+
+/// Serve an embedded web asset.
+fn asset(path: &str) -> Result<Response<Full<Bytes>>> {
+    let content_type = match path.rsplit_once('.').map(|(_, ext)| ext) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("js") => "text/javascript; charset=utf-8",
+        _ => "application/octet-stream",
+    };
+    Ok(match WebMetadata::get(path) {
+        Some(file) => response(StatusCode::OK, content_type, file.data.into_owned()),
+        None => response(StatusCode::NOT_FOUND, "text/plain", "Not found"),
+    })
+}
+
+/// Build a response for a generated epub.
+fn epub_response(data: Vec<u8>) -> Response<Full<Bytes>> {
+    let mut res = response(StatusCode::OK, "application/epub+zip", data);
+    res.headers_mut().insert(
+        hyper::header::CONTENT_DISPOSITION,
+        hyper::header::HeaderValue::from_static("attachment; filename=\"book.epub\""),
+    );
+    res
+}
+
+/// Build a response.
+fn response(
+    status: StatusCode,
+    content_type: &'static str,
+    body: impl Into<Bytes>,
+) -> Response<Full<Bytes>> {
+    let mut res = Response::new(Full::new(body.into()));
+    *res.status_mut() = status;
+    res.headers_mut().insert(
+        hyper::header::CONTENT_TYPE,
+        hyper::header::HeaderValue::from_static(content_type),
+    );
+    res
+}
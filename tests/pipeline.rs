@@ -0,0 +1,73 @@
+//! Runs the library pipeline from configuration to exported book.
+
+use std::path::PathBuf;
+
+/// Write a Chinese chapter and a configuration converting it, returning the
+/// configuration file.
+fn write_book(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("jdpub-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let chapter = dir.join("chapter.html");
+    std::fs::write(
+        &chapter,
+        "<html><body><h1>第一章</h1><p>中国的传统文化 &amp; 历史。</p></body></html>",
+    )
+    .unwrap();
+
+    let config = dir.join("book.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "[language]\n\
+            language = \"chinese\"\n\
+            [language.chinese]\n\
+            definitions = 4\n\
+            pinyin = 4\n\
+            [[import.chapters]]\n\
+            uri = {:?}\n\
+            title = \"第一章\"\n\
+            [export]\n\
+            title = \"Test & Book\"\n\
+            readings = \"parentheses\"\n",
+            chapter.to_string_lossy()
+        ),
+    )
+    .unwrap();
+    config
+}
+
+/// Import, annotate and generate a book in memory.
+async fn generate(name: &str, output: &str) -> String {
+    let path = write_book(name);
+    let config = jdpub::Pipeline::load_config([&path], false).unwrap();
+    let mut pipeline = jdpub::Pipeline::new(config).unwrap();
+    pipeline.config_mut().export.output_file = PathBuf::from(output);
+
+    let mut book = pipeline.import().await.unwrap();
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    assert_eq!(book.chapters.len(), 1);
+    pipeline.annotate(&mut book).unwrap();
+    String::from_utf8(pipeline.generate(&mut book).unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn generates_text() {
+    let text = generate("text", "book.txt").await;
+
+    // 传统 is HSK 5, above the thresholds, while 中国 is HSK 1.
+    assert!(text.contains("传统(chuántǒng)"), "{text}");
+    assert!(text.contains("传统[chuántǒng::tradition::HSK 5]"), "{text}");
+    assert!(!text.contains("中国("), "{text}");
+    assert!(text.contains("& 历史"), "{text}");
+}
+
+#[tokio::test]
+async fn generates_html() {
+    let html = generate("html", "book.html").await;
+
+    assert!(html.contains("<title>Test &amp; Book</title>"), "{html}");
+    assert!(html.contains("<rt>chuántǒng</rt>"), "{html}");
+    assert!(html.contains("tradition"), "{html}");
+    assert!(html.contains("&amp; "), "{html}");
+}